@@ -0,0 +1,233 @@
+//! Century tracking for RTC calendar, which keeps only two digits of the year.
+//!
+//! Without tracking, two digit year is placed in a window of 100 years that starts with base
+//! year (2000 - 2099 by default). With tracking enabled, century is kept in one of the RTC
+//! backup registers together with the last seen two digit year, so 99 -> 00 rollover is
+//! detected and calendar works from base year up to year 9999.
+//!
+//! RTC hardware treats every year divisible by 4 as leap year, so in century years that are
+//! not leap (2100, 2200, 2300) it inserts 29 February. Reads return the real date, RTC calendar
+//! is moved one day forward by **update_century()**. Without tracking it has to be called
+//! on 29 February.
+//!
+//! Reads never write into RTC, so they can be done from interrupts and loggers.
+//! **update_century()** saves rollover and leap day correction, it has to be called from the
+//! main loop at least once a day.
+//!
+//! **Note:** Backup registers are reset by **start_clock()**, century has to be set again
+//! with **set_date()** after it. Please use **resume_clock()** to keep them over MCU reset.
+//!
+//! ## Usage:
+//! ```
+//! use stm32f3_rtc::datetime::{Date, DateAccess};
+//! use stm32f3_rtc::rtc::Rtc;
+//! use stm32f3xx_hal::pac;
+//!
+//! let mut peripheral = pac::Peripherals::take().unwrap();
+//! let mut rtc = Rtc::new(peripheral.RTC);
+//! rtc.start_clock(&mut peripheral.PWR, &mut peripheral.RCC);
+//! rtc.set_base_year(1980);
+//! rtc.track_century(0);
+//! rtc.set_date(Date::from(31, 12, 2099));
+//! loop {
+//!     rtc.update_century();
+//!     rtc.delay(60);
+//! }
+//! ```
+use core::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use cortex_m::interrupt::free;
+use datetime::{BcdDate, ConversionError, Date};
+use rtc::{init_mode, short_date_from_register, unprotected, write_date, Rtc};
+use stm32f3xx_hal::pac::rtc::RegisterBlock;
+
+/// Number of RTC backup registers
+const BACKUP_REGISTERS: usize = 16;
+/// Last supported year when century is tracked
+const MAX_YEAR: u32 = 9999;
+/// Marks backup register that keeps valid century
+const MAGIC: u32 = 0xC5;
+/// Set when 29 February of current non leap century year was already skipped
+const LEAP_DAY_SKIPPED: u32 = 1 << 23;
+
+static BASE_YEAR: AtomicU32 = AtomicU32::new(2000);
+/// Backup register that keeps century, BACKUP_REGISTERS when century is not tracked
+static REGISTER: AtomicUsize = AtomicUsize::new(BACKUP_REGISTERS);
+
+impl Rtc {
+    /// Set the first year, that calendar can keep (2000 by default). Without century tracking
+    /// years from base year to base year + 99 are supported, so base year is limited to 9900.
+    ///
+    /// **Note:** Base year is global for the whole program, not kept by this Rtc. It has to
+    /// be set once, before **start_clock()**.
+    pub fn set_base_year(&mut self, base_year: u32) -> &Self {
+        BASE_YEAR.store(base_year.min(MAX_YEAR - 99), Ordering::Relaxed);
+        self
+    }
+
+    /// Keep century in given backup register (0 - 15), so years from base year to 9999 are
+    /// supported. When register does not keep century yet, it is taken from base year window.
+    ///
+    /// **Note:** Register is global for the whole program, not kept by this Rtc. It has to
+    /// be set once, before **start_clock()**.
+    pub fn track_century(&mut self, register: usize) -> &Self {
+        assert!(
+            register < BACKUP_REGISTERS,
+            "RTC has only 16 backup registers"
+        );
+        REGISTER.store(register, Ordering::Relaxed);
+        self
+    }
+
+    /// Saves century rollover (99 -> 00) into backup register and removes 29 February, that
+    /// RTC inserts into non leap century years. Calendar reads only take them into account
+    /// without writing anything, so this has to be called from the main loop (not from an
+    /// interrupt, it enters init mode) at least once a day.
+    /// ```
+    /// loop {
+    ///     rtc.update_century();
+    ///     rtc.delay(60);
+    /// }
+    /// ```
+    pub fn update_century(&mut self) {
+        update(&self.rtc)
+    }
+
+    /// Returns range of years that calendar can keep
+    /// ```
+    /// let (first, last) = rtc.year_range();
+    /// hprintln!("Years {} - {}", first, last);
+    /// ```
+    pub fn year_range(&self) -> (u32, u32) {
+        year_range()
+    }
+}
+
+/// Returns backup register that keeps century, when it is tracked
+pub(crate) fn tracked_register() -> Option<usize> {
+    match REGISTER.load(Ordering::Relaxed) {
+        BACKUP_REGISTERS => None,
+        register => Some(register),
+    }
+}
+
+/// Returns first and last supported year
+pub(crate) fn year_range() -> (u32, u32) {
+    let base_year = BASE_YEAR.load(Ordering::Relaxed);
+    match tracked_register() {
+        Some(_) => (base_year, MAX_YEAR),
+        None => (base_year, base_year + 99),
+    }
+}
+
+/// Returns year, when it is in range that calendar can keep
+pub(crate) fn check_year(year: i64) -> Result<u32, ConversionError> {
+    let (first, last) = year_range();
+    match year >= i64::from(first) && year <= i64::from(last) {
+        true => Ok(year as u32),
+        false => Err(ConversionError::YearOutOfRange),
+    }
+}
+
+/// Returns full year for two digit year, that is placed in base year window
+fn window_year(year: u32) -> u32 {
+    let base_year = BASE_YEAR.load(Ordering::Relaxed);
+    let century = base_year - base_year % 100;
+    match year >= base_year % 100 {
+        true => century + year,
+        false => century + 100 + year,
+    }
+}
+
+/// Returns full year and leap day state for date read from RTC, that has only two digits
+/// of the year
+fn century_state(rtc: &RegisterBlock, date: &Date) -> (u32, bool) {
+    let short_year = date.year;
+    match tracked_register() {
+        Some(register) => {
+            let value = rtc.bkpr[register].read().bits();
+            let (century, skipped) = match (value >> 24 == MAGIC, value & 0xFF) {
+                // Two digit year went back, so it rolled over 99 -> 00
+                (true, last_year) if short_year < last_year => {
+                    (((value >> 8) & 0x7FFF) * 100 + 100, false)
+                }
+                (true, _) => (((value >> 8) & 0x7FFF) * 100, value & LEAP_DAY_SKIPPED != 0),
+                // Century is not tracked yet, RTC date is assumed to be correct
+                (false, _) => (window_year(short_year) - short_year, date.month > 2),
+            };
+            (century + short_year, skipped)
+        }
+        None => (window_year(short_year), date.month != 2 || date.day != 29),
+    }
+}
+
+/// Returns real date, when RTC inserted 29 February into non leap century year and it was
+/// not skipped yet
+#[allow(clippy::manual_is_multiple_of)]
+fn leap_day_correction(date: &Date, skipped: bool) -> Option<Date> {
+    let inserted = date.month > 2 || (date.month == 2 && date.day == 29);
+    if date.year % 100 != 0 || Date::is_leap_year(date.year) || skipped || !inserted {
+        return None;
+    }
+    match date.day == 29 && date.month == 2 {
+        true => Some(Date::from(1, 3, date.year)),
+        false => Some(date.next_day()),
+    }
+}
+
+/// Returns date with full year for date read from RTC, that has only two digits of the year.
+/// Nothing is written, century rollover and skipped leap day are only taken into account
+/// until **update_century()** saves them.
+pub(crate) fn full_date(rtc: &RegisterBlock, date: Date) -> Date {
+    let (year, skipped) = century_state(rtc, &date);
+    let date = Date::from(date.day, date.month, year);
+    leap_day_correction(&date, skipped).unwrap_or(date)
+}
+
+/// Saves century rollover into backup register and writes skipped leap day into RTC
+pub(crate) fn update(rtc: &RegisterBlock) {
+    free(|_| {
+        let date = short_date_from_register(&rtc.dr.read());
+        let (year, skipped) = century_state(rtc, &date);
+        let date = Date::from(date.day, date.month, year);
+        match (leap_day_correction(&date, skipped), tracked_register()) {
+            (Some(corrected), _) => correct(rtc, &corrected),
+            (None, Some(register)) => save(rtc, register, year, skipped),
+            (None, None) => {}
+        }
+    })
+}
+
+/// Saves century of the date that is written into RTC, dates with year out of supported
+/// range are rejected and nothing is saved
+pub(crate) fn store(rtc: &RegisterBlock, date: &Date) -> Result<Date, ConversionError> {
+    let year = check_year(i64::from(date.year))?;
+    if let Some(register) = tracked_register() {
+        // Leap day cannot be inserted any more, when date is set after February
+        free(|_| save(rtc, register, year, date.month > 2));
+    }
+    Ok(*date)
+}
+
+/// Writes century, last seen two digit year and leap day state into backup register,
+/// when they changed. It has to be called in critical section, so the register is not
+/// changed between read and write.
+fn save(rtc: &RegisterBlock, register: usize, year: u32, skipped: bool) {
+    let mut value = (MAGIC << 24) | ((year / 100) << 8) | (year % 100);
+    if skipped {
+        value |= LEAP_DAY_SKIPPED;
+    }
+    if rtc.bkpr[register].read().bits() != value {
+        rtc.bkpr[register].write(|w| w.bits(value));
+    }
+}
+
+/// Writes corrected date into RTC
+fn correct(rtc: &RegisterBlock, date: &Date) {
+    init_mode(rtc, |rtc| write_date(rtc, &BcdDate::from(*date)));
+    unprotected(rtc, |rtc| rtc.isr.modify(|_, w| w.rsf().clear_bit()));
+    // Wait until shadow registers keep corrected date, so next read does not correct it again
+    while rtc.isr.read().rsf().bit_is_clear() {}
+    if let Some(register) = tracked_register() {
+        save(rtc, register, date.year, true);
+    }
+}
@@ -0,0 +1,119 @@
+//! Conversions between [datetime](crate::datetime) types and [chrono](https://docs.rs/chrono)
+//! **NaiveDate**, **NaiveTime** and **NaiveDateTime**, together with [Rtc] methods that
+//! set and read calendar directly as chrono types.
+//!
+//! ## Usage:
+//! ```
+//! use chrono::NaiveDate;
+//! use stm32f3_rtc::rtc::Rtc;
+//! use stm32f3xx_hal::pac;
+//!
+//! let mut peripheral = pac::Peripherals::take().unwrap();
+//! let mut rtc = Rtc::new(peripheral.RTC);
+//! rtc.start_clock(&mut peripheral.PWR, &mut peripheral.RCC);
+//! rtc.set_naive_datetime(
+//!     &NaiveDate::from_ymd_opt(2024, 6, 1)
+//!         .unwrap()
+//!         .and_hms_milli_opt(12, 30, 0, 500)
+//!         .unwrap(),
+//! )
+//! .unwrap();
+//! let now = rtc.naive_datetime().unwrap();
+//! ```
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use core::convert::TryFrom;
+use datetime::{Date, DateTime, Time};
+use rtc::Rtc;
+
+/// Errors returned by chrono conversions, shared with other conversions of the crate
+pub use datetime::ConversionError as Error;
+
+impl TryFrom<Date> for NaiveDate {
+    type Error = Error;
+
+    fn try_from(date: Date) -> Result<Self, Self::Error> {
+        let year = i32::try_from(date.year).map_err(|_| Error::InvalidInputData)?;
+        NaiveDate::from_ymd_opt(year, u32::from(date.month), u32::from(date.day))
+            .ok_or(Error::InvalidInputData)
+    }
+}
+
+impl TryFrom<NaiveDate> for Date {
+    type Error = Error;
+
+    fn try_from(date: NaiveDate) -> Result<Self, Self::Error> {
+        let year = u32::try_from(date.year()).map_err(|_| Error::InvalidInputData)?;
+        Ok(Date::from(date.day() as u8, date.month() as u8, year))
+    }
+}
+
+impl TryFrom<Time> for NaiveTime {
+    type Error = Error;
+
+    fn try_from(time: Time) -> Result<Self, Self::Error> {
+        NaiveTime::from_hms_opt(
+            u32::from(time.hour),
+            u32::from(time.minute),
+            u32::from(time.second),
+        )
+        .ok_or(Error::InvalidInputData)
+    }
+}
+
+impl TryFrom<NaiveTime> for Time {
+    type Error = Error;
+
+    /// Create Time from NaiveTime, returns error when it has fraction of second (also for
+    /// leap second). Please use **DateTime** conversion to keep it.
+    fn try_from(time: NaiveTime) -> Result<Self, Self::Error> {
+        match time.nanosecond() {
+            0 => Ok(whole_seconds(&time)),
+            _ => Err(Error::InvalidInputData),
+        }
+    }
+}
+
+/// Returns time without fraction of second, that is kept separately by **DateTime**
+fn whole_seconds(time: &NaiveTime) -> Time {
+    Time::from(time.hour() as u8, time.minute() as u8, time.second() as u8)
+}
+
+impl TryFrom<DateTime> for NaiveDateTime {
+    type Error = Error;
+
+    fn try_from(date_time: DateTime) -> Result<Self, Self::Error> {
+        let time = NaiveTime::try_from(date_time.time)?
+            .with_nanosecond(date_time.nanosecond)
+            .ok_or(Error::InvalidInputData)?;
+        Ok(NaiveDate::try_from(date_time.date)?.and_time(time))
+    }
+}
+
+impl TryFrom<NaiveDateTime> for DateTime {
+    type Error = Error;
+
+    /// Create DateTime from NaiveDateTime, leap second is kept as the last nanosecond
+    /// of 59th second
+    fn try_from(date_time: NaiveDateTime) -> Result<Self, Self::Error> {
+        Ok(DateTime::from(
+            Date::try_from(date_time.date())?,
+            whole_seconds(&date_time.time()),
+            date_time.nanosecond().min(999_999_999),
+        ))
+    }
+}
+
+impl Rtc {
+    /// Returns current date and time as NaiveDateTime, fraction of second is kept
+    /// as nanoseconds
+    pub fn naive_datetime(&self) -> Result<NaiveDateTime, Error> {
+        NaiveDateTime::try_from(self.date_time())
+    }
+
+    /// Set date and time from NaiveDateTime, including fraction of second
+    ///
+    /// **Note:** Returns error if year is out of range given by **Rtc::year_range()**
+    pub fn set_naive_datetime(&mut self, date_time: &NaiveDateTime) -> Result<(), Error> {
+        self.set_date_time(DateTime::try_from(*date_time)?)
+    }
+}
@@ -0,0 +1,71 @@
+//! Read only clock handle, that can be copied into loggers, drivers and interrupt handlers,
+//! which only need to know what time it is.
+//!
+//! Reads are done in critical section like all calendar reads of the crate, because shadow
+//! registers are locked by one read and unlocked by another, so reads from interrupt in between
+//! would break coherency.
+//!
+//! ## Usage:
+//! ```
+//! use stm32f3_rtc::clock::RtcClock;
+//! use stm32f3_rtc::rtc::Rtc;
+//! use stm32f3xx_hal::pac;
+//! use cortex_m_semihosting::hprintln;
+//!
+//! let mut peripheral = pac::Peripherals::take().unwrap();
+//! let mut rtc = Rtc::new(peripheral.RTC);
+//! rtc.start_clock(&mut peripheral.PWR, &mut peripheral.RCC);
+//! let clock: RtcClock = rtc.clock();
+//! hprintln!("[{}] started", clock.date_time());
+//! ```
+use datetime::{Date, DateTime, Time};
+use parts::Calendar;
+use rtc::{read_calendar, read_date_time, Rtc};
+use stm32f3xx_hal::pac::rtc::RegisterBlock;
+use stm32f3xx_hal::pac::RTC;
+
+/// Copyable handle that reads RTC calendar, it can be created only from Rtc or its calendar
+#[derive(Clone, Copy, Debug)]
+pub struct RtcClock {
+    _private: (),
+}
+
+impl RtcClock {
+    /// Returns clock handle for places that are called without Rtc (e.g. defmt timestamp)
+    pub(crate) fn new() -> RtcClock {
+        RtcClock { _private: () }
+    }
+
+    fn registers(&self) -> &'static RegisterBlock {
+        unsafe { &*RTC::PTR }
+    }
+
+    /// Returns current time in 24 hour format
+    pub fn time(&self) -> Time {
+        read_calendar(self.registers()).1
+    }
+
+    /// Returns current date
+    pub fn date(&self) -> Date {
+        read_calendar(self.registers()).0
+    }
+
+    /// Returns date and time read at once, including fraction of second
+    pub fn date_time(&self) -> DateTime {
+        read_date_time(self.registers())
+    }
+}
+
+impl Rtc {
+    /// Returns read only clock handle
+    pub fn clock(&self) -> RtcClock {
+        RtcClock::new()
+    }
+}
+
+impl Calendar {
+    /// Returns read only clock handle
+    pub fn clock(&self) -> RtcClock {
+        RtcClock::new()
+    }
+}
@@ -1,3 +1,5 @@
+use core::fmt;
+
 /// Trait that determinate time write and access
 pub trait TimeAccess {
     fn time(&self) -> Time;
@@ -10,7 +12,17 @@ pub trait DateAccess {
     fn set_date(&mut self, date: Date);
 }
 
+/// Errors of conversions between calendar types of this crate and types of other crates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConversionError {
+    /// Value cannot be represented by target type (e.g. 30 February or negative year)
+    InvalidInputData,
+    /// Year is out of range that RTC can keep, see **Rtc::year_range()**
+    YearOutOfRange,
+}
+
 /// Keeps date in struct with easy access
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Date {
     pub day: u8,
     pub month: u8,
@@ -20,12 +32,370 @@ pub struct Date {
 impl Date {
     /// Create a new Date struct from fallowing arguments,
     /// (day, month, year)
-    pub fn from(day: u8, month: u8, year: u32) -> Date {
+    pub const fn from(day: u8, month: u8, year: u32) -> Date {
         Date { day, month, year }
     }
+
+    /// Returns date packed into DOS/FAT date field:
+    /// **bits 15-9** year since 1980, **bits 8-5** month, **bits 4-0** day.
+    ///
+    /// **Note:** FAT keeps years between 1980 and 2107, other years are clamped to this range
+    /// ```
+    /// use stm32f3_rtc::datetime::Date;
+    /// assert_eq!(Date::from(1, 6, 2024).to_fat(), 0x58C1);
+    /// ```
+    pub fn to_fat(&self) -> u16 {
+        let year = self.year.clamp(1980, 2107) - 1980;
+        ((year as u16) << 9) | (u16::from(self.month & 0x0F) << 5) | u16::from(self.day & 0x1F)
+    }
+
+    /// Create a new Date struct from DOS/FAT date field
+    /// ```
+    /// use stm32f3_rtc::datetime::Date;
+    /// let date = Date::from_fat(0x58C1);
+    /// assert_eq!((date.day, date.month, date.year), (1, 6, 2024));
+    /// ```
+    pub fn from_fat(date: u16) -> Date {
+        Date {
+            day: (date & 0x1F) as u8,
+            month: ((date >> 5) & 0x0F) as u8,
+            year: u32::from(date >> 9) + 1980,
+        }
+    }
+
+    /// Returns true for leap years of Gregorian calendar
+    /// ```
+    /// use stm32f3_rtc::datetime::Date;
+    /// assert!(Date::is_leap_year(2000) && Date::is_leap_year(2024));
+    /// assert!(!Date::is_leap_year(2100) && !Date::is_leap_year(2023));
+    /// ```
+    // u32::is_multiple_of needs Rust 1.87
+    #[allow(clippy::manual_is_multiple_of)]
+    pub const fn is_leap_year(year: u32) -> bool {
+        (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+    }
+
+    /// Returns number of days in given month, 0 for invalid month
+    /// ```
+    /// use stm32f3_rtc::datetime::Date;
+    /// assert_eq!(Date::days_in_month(2024, 2), 29);
+    /// assert_eq!(Date::days_in_month(2100, 2), 28);
+    /// assert_eq!(Date::days_in_month(2024, 13), 0);
+    /// ```
+    pub const fn days_in_month(year: u32, month: u8) -> u8 {
+        match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if Self::is_leap_year(year) => 29,
+            2 => 28,
+            _ => 0,
+        }
+    }
+
+    /// Returns day of the year, 1 for 1 January, 0 for invalid month
+    /// ```
+    /// use stm32f3_rtc::datetime::Date;
+    /// assert_eq!(Date::from(31, 12, 2024).day_of_year(), 366);
+    /// ```
+    pub const fn day_of_year(&self) -> u16 {
+        const DAYS_BEFORE_MONTH: [u16; 12] =
+            [0, 31, 59, 90, 120, 151, 181, 212, 243, 273, 304, 334];
+        if self.month < 1 || self.month > 12 {
+            return 0;
+        }
+        let leap_day = (self.month > 2 && Self::is_leap_year(self.year)) as u16;
+        DAYS_BEFORE_MONTH[self.month as usize - 1] + leap_day + self.day as u16
+    }
+
+    /// Returns ISO 8601 week date as (week-based year, week number 1 - 53). Week starts
+    /// on Monday and the first week of the year is the one with its first Thursday.
+    ///
+    /// **Note:** Years before year 0 cannot be returned, so first days of year 0, that belong
+    /// to the last week of year -1, are returned as (0, 0)
+    /// ```
+    /// use stm32f3_rtc::datetime::Date;
+    /// assert_eq!(Date::from(1, 1, 2021).iso_week(), (2020, 53));
+    /// assert_eq!(Date::from(30, 12, 2024).iso_week(), (2025, 1));
+    /// assert_eq!(Date::from(1, 6, 2024).iso_week(), (2024, 22));
+    /// ```
+    pub const fn iso_week(&self) -> (u32, u8) {
+        let weekday = self.weekday().number_from_monday() as u16;
+        let week = (self.day_of_year() + 10 - weekday) / 7;
+        if week < 1 && self.year == 0 {
+            (0, 0)
+        } else if week < 1 {
+            (self.year - 1, Self::iso_weeks_in_year(self.year - 1))
+        } else if week as u8 > Self::iso_weeks_in_year(self.year) {
+            (self.year + 1, 1)
+        } else {
+            (self.year, week as u8)
+        }
+    }
+
+    /// Returns number of ISO 8601 weeks in given year (52 or 53)
+    const fn iso_weeks_in_year(year: u32) -> u8 {
+        let first = Date::from(1, 1, year).weekday();
+        match (first, Self::is_leap_year(year)) {
+            (Weekday::Thursday, _) | (Weekday::Wednesday, true) => 53,
+            _ => 52,
+        }
+    }
+
+    /// Returns n-th (1 - 5) given weekday of the month, None when month does not have it
+    /// ```
+    /// use stm32f3_rtc::datetime::{Date, Weekday};
+    /// assert_eq!(
+    ///     Date::nth_weekday(2024, 3, 2, Weekday::Sunday),
+    ///     Some(Date::from(10, 3, 2024))
+    /// );
+    /// assert_eq!(Date::nth_weekday(2024, 2, 5, Weekday::Monday), None);
+    /// ```
+    pub const fn nth_weekday(year: u32, month: u8, n: u8, weekday: Weekday) -> Option<Date> {
+        if n < 1 || n > 5 {
+            return None;
+        }
+        let first = Date::from(1, month, year).weekday();
+        let offset = (7 + weekday.days_from_sunday() - first.days_from_sunday()) % 7;
+        let day = 1 + offset + (n - 1) * 7;
+        match day <= Self::days_in_month(year, month) {
+            true => Some(Date::from(day, month, year)),
+            false => None,
+        }
+    }
+
+    /// Returns the last given weekday of the month
+    /// ```
+    /// use stm32f3_rtc::datetime::{Date, Weekday};
+    /// assert_eq!(Date::last_weekday(2024, 10, Weekday::Sunday), Date::from(27, 10, 2024));
+    /// ```
+    pub const fn last_weekday(year: u32, month: u8, weekday: Weekday) -> Date {
+        match Self::nth_weekday(year, month, 5, weekday) {
+            Some(date) => date,
+            None => match Self::nth_weekday(year, month, 4, weekday) {
+                Some(date) => date,
+                // Month is invalid
+                None => Date::from(0, month, year),
+            },
+        }
+    }
+
+    /// Returns the following day
+    pub(crate) fn next_day(&self) -> Date {
+        match (
+            self.day < Self::days_in_month(self.year, self.month),
+            self.month,
+        ) {
+            (true, _) => Date::from(self.day + 1, self.month, self.year),
+            (false, 12) => Date::from(1, 1, self.year + 1),
+            (false, _) => Date::from(1, self.month + 1, self.year),
+        }
+    }
+
+    /// Returns the previous day
+    pub(crate) fn previous_day(&self) -> Date {
+        match (self.day > 1, self.month) {
+            (true, _) => Date::from(self.day - 1, self.month, self.year),
+            (false, 1) => Date::from(31, 12, self.year - 1),
+            (false, _) => Date::from(
+                Self::days_in_month(self.year, self.month - 1),
+                self.month - 1,
+                self.year,
+            ),
+        }
+    }
+
+    /// Returns day of the week calculated from the date
+    /// ```
+    /// use stm32f3_rtc::datetime::{Date, Weekday};
+    /// assert_eq!(Date::from(1, 6, 2024).weekday(), Weekday::Saturday);
+    /// assert_eq!(Date::from(1, 1, 2000).weekday(), Weekday::Saturday);
+    /// ```
+    pub const fn weekday(&self) -> Weekday {
+        const OFFSETS: [u32; 12] = [0, 3, 2, 5, 0, 3, 5, 1, 4, 6, 2, 4];
+        // 400 years keep the same weekdays, it prevents underflow for January of year 0
+        let year = self.year + 400 - (self.month < 3) as u32;
+        let month = match self.month {
+            1..=12 => self.month as usize - 1,
+            _ => 0,
+        };
+        let days_from_sunday =
+            (year + year / 4 - year / 100 + year / 400 + OFFSETS[month] + self.day as u32) % 7;
+        Weekday::from_days_from_sunday(days_from_sunday as u8)
+    }
+
+    /// Returns Julian Day Number, number of days since 1 January 4713 BC of proleptic
+    /// Julian calendar (it changes at noon, value is for the noon of this date)
+    /// ```
+    /// use stm32f3_rtc::datetime::Date;
+    /// assert_eq!(Date::from(1, 1, 2000).julian_day_number(), 2_451_545);
+    /// ```
+    pub const fn julian_day_number(&self) -> i32 {
+        // Days are counted from 1 March of year -400, so January and February of year 0
+        // are not negative. Year starts in March, so leap day is its last day.
+        let year = self.year as i32 + 400 - (self.month <= 2) as i32;
+        let month = (self.month as i32 + 9) % 12;
+        let era = year / 400;
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * month + 2) / 5 + self.day as i32 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era + JDN_OF_MARCH_400_BC
+    }
+
+    /// Create a new Date from Julian Day Number, returns None for days before year 0
+    pub const fn from_julian_day_number(jdn: i32) -> Option<Date> {
+        if jdn < JDN_OF_YEAR_0 {
+            return None;
+        }
+        let days = jdn - JDN_OF_MARCH_400_BC;
+        let era = days / 146_097;
+        let day_of_era = days - era * 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * month + 2) / 5 + 1) as u8;
+        let month = match month < 10 {
+            true => month + 3,
+            false => month - 9,
+        } as u8;
+        let year = year_of_era + era * 400 + (month <= 2) as i32 - 400;
+        Some(Date::from(day, month, year as u32))
+    }
+
+    /// Returns Modified Julian Day (days since 17 November 1858)
+    /// ```
+    /// use stm32f3_rtc::datetime::Date;
+    /// assert_eq!(Date::from(1, 1, 2000).modified_julian_day(), 51_544);
+    /// assert_eq!(Date::from_modified_julian_day(0), Some(Date::from(17, 11, 1858)));
+    /// ```
+    pub const fn modified_julian_day(&self) -> i32 {
+        self.julian_day_number() - MJD_OFFSET
+    }
+
+    /// Create a new Date from Modified Julian Day, returns None for days before year 0
+    pub const fn from_modified_julian_day(mjd: i32) -> Option<Date> {
+        Self::from_julian_day_number(mjd + MJD_OFFSET)
+    }
+
+    /// Returns number of days elapsed since epoch date, negative for dates before it
+    /// ```
+    /// use stm32f3_rtc::datetime::Date;
+    /// const EPOCH: Date = Date::from(1, 1, 2000);
+    /// assert_eq!(Date::from(1, 6, 2024).days_since(&EPOCH), 8_918);
+    /// assert_eq!(Date::from(1, 1, 1970).days_since(&EPOCH), -10_957);
+    /// assert_eq!(Date::from_days_since(&EPOCH, 8_918), Some(Date::from(1, 6, 2024)));
+    /// ```
+    pub const fn days_since(&self, epoch: &Date) -> i32 {
+        self.julian_day_number() - epoch.julian_day_number()
+    }
+
+    /// Create a new Date from number of days elapsed since epoch date, returns None for
+    /// days before year 0
+    pub const fn from_days_since(epoch: &Date, days: i32) -> Option<Date> {
+        Self::from_julian_day_number(epoch.julian_day_number() + days)
+    }
+}
+
+/// Julian Day Number of 1 March of year -400
+const JDN_OF_MARCH_400_BC: i32 = 1_575_023;
+/// Julian Day Number of 1 January of year 0
+const JDN_OF_YEAR_0: i32 = 1_721_060;
+/// Difference between Julian Day Number and Modified Julian Day
+const MJD_OFFSET: i32 = 2_400_001;
+/// Nanoseconds in a day
+const NANOSECONDS_PER_DAY: u64 = 86_400_000_000_000;
+/// Nanoseconds in a day divided by 2^16, it keeps fixed point conversions within u64
+const NANOSECONDS_PER_DAY_SHIFTED: u64 = 1_318_359_375;
+
+/// Julian Date (or Modified Julian Date) as whole days and fixed point fraction of the day,
+/// so it keeps ~20 us resolution without 64 bit floating point math
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct JulianDate {
+    pub day: i32,
+    /// Fraction of the day in 1/2^32 units
+    pub fraction: u32,
+}
+
+impl JulianDate {
+    /// Returns Julian Date as floating point number of days
+    pub fn to_f64(&self) -> f64 {
+        f64::from(self.day) + f64::from(self.fraction) / 4_294_967_296.0
+    }
+
+    /// Create fraction of the day from nanoseconds elapsed since its beginning
+    fn from_nanoseconds(day: i32, nanoseconds: u64) -> JulianDate {
+        let fraction =
+            ((nanoseconds << 16) + NANOSECONDS_PER_DAY_SHIFTED / 2) / NANOSECONDS_PER_DAY_SHIFTED;
+        match fraction > u64::from(u32::MAX) {
+            true => JulianDate {
+                day: day + 1,
+                fraction: 0,
+            },
+            false => JulianDate {
+                day,
+                fraction: fraction as u32,
+            },
+        }
+    }
+
+    /// Returns nanoseconds elapsed since beginning of the day
+    fn nanoseconds(&self) -> u64 {
+        (u64::from(self.fraction) * NANOSECONDS_PER_DAY_SHIFTED + (1 << 15)) >> 16
+    }
+}
+
+/// Day of the week, numbered as in RTC date register (1 - Monday ... 7 - Sunday)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Weekday {
+    Monday = 1,
+    Tuesday = 2,
+    Wednesday = 3,
+    Thursday = 4,
+    Friday = 5,
+    Saturday = 6,
+    Sunday = 7,
+}
+
+impl Weekday {
+    /// Returns weekday number, 1 - Monday ... 7 - Sunday
+    pub const fn number_from_monday(self) -> u8 {
+        self as u8
+    }
+
+    /// Returns number of days since Sunday, 0 - Sunday ... 6 - Saturday
+    pub const fn days_from_sunday(self) -> u8 {
+        self as u8 % 7
+    }
+
+    /// Create weekday from number of days since Sunday, values are taken modulo 7
+    pub const fn from_days_from_sunday(days: u8) -> Weekday {
+        match days % 7 {
+            0 => Weekday::Sunday,
+            1 => Weekday::Monday,
+            2 => Weekday::Tuesday,
+            3 => Weekday::Wednesday,
+            4 => Weekday::Thursday,
+            5 => Weekday::Friday,
+            _ => Weekday::Saturday,
+        }
+    }
+
+    /// Returns English name of the weekday
+    pub fn name(self) -> &'static str {
+        match self {
+            Weekday::Monday => "Monday",
+            Weekday::Tuesday => "Tuesday",
+            Weekday::Wednesday => "Wednesday",
+            Weekday::Thursday => "Thursday",
+            Weekday::Friday => "Friday",
+            Weekday::Saturday => "Saturday",
+            Weekday::Sunday => "Sunday",
+        }
+    }
 }
 
 /// Keeps time in struct with easy access
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Time {
     pub hour: u8,
     pub minute: u8,
@@ -47,9 +417,299 @@ impl Time {
     pub fn to_seconds(&self) -> u32 {
         (self.hour as u32) * 3600 + (self.minute as u32) * 60 + (self.second as u32)
     }
+
+    /// Returns time packed into DOS/FAT time field:
+    /// **bits 15-11** hour, **bits 10-5** minute, **bits 4-0** second divided by 2.
+    ///
+    /// **Note:** FAT time has 2 second resolution, odd second is kept by [Time::to_fat_fine]
+    /// ```
+    /// use stm32f3_rtc::datetime::Time;
+    /// assert_eq!(Time::from(12, 30, 15).to_fat(), 0x63C7);
+    /// ```
+    pub fn to_fat(&self) -> u16 {
+        (u16::from(self.hour & 0x1F) << 11)
+            | (u16::from(self.minute & 0x3F) << 5)
+            | u16::from(self.second / 2)
+    }
+
+    /// Returns FAT fine resolution field (creation time in 10 ms units, 0 - 199), it keeps
+    /// odd second lost by [Time::to_fat] and milliseconds (e.g. from **Rtc::milliseconds()**)
+    /// ```
+    /// use stm32f3_rtc::datetime::Time;
+    /// assert_eq!(Time::from(12, 30, 15).to_fat_fine(250), 125);
+    /// ```
+    pub fn to_fat_fine(&self, millisecond: u16) -> u8 {
+        (self.second % 2) * 100 + (millisecond.min(999) / 10) as u8
+    }
+
+    /// Create a new Time struct from DOS/FAT time field, seconds are always even
+    /// ```
+    /// use stm32f3_rtc::datetime::Time;
+    /// let time = Time::from_fat(0x63C7);
+    /// assert_eq!((time.hour, time.minute, time.second), (12, 30, 14));
+    /// ```
+    pub fn from_fat(time: u16) -> Time {
+        Time {
+            hour: (time >> 11) as u8,
+            minute: ((time >> 5) & 0x3F) as u8,
+            second: ((time & 0x1F) * 2) as u8,
+        }
+    }
+
+    /// Create a new Time struct from DOS/FAT time and fine resolution fields.
+    /// Returns time together with milliseconds
+    /// ```
+    /// use stm32f3_rtc::datetime::Time;
+    /// let (time, millisecond) = Time::from_fat_fine(0x63C7, 125);
+    /// assert_eq!((time.second, millisecond), (15, 250));
+    /// ```
+    pub fn from_fat_fine(time: u16, fine: u8) -> (Time, u16) {
+        let mut result = Self::from_fat(time);
+        let fine = fine.min(199);
+        result.second += fine / 100;
+        (result, u16::from(fine % 100) * 10)
+    }
+}
+
+/// Half of the day used by 12 hour clock
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AmPm {
+    AM,
+    PM,
+}
+
+/// Keeps time in 12 hour format (hour 1 - 12 with AM/PM)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Time12 {
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    pub am_pm: AmPm,
+}
+
+impl Time12 {
+    /// Create a new Time12 struct from following arguments
+    /// (hour, minute, second, am_pm)
+    pub fn from(hour: u8, minute: u8, second: u8, am_pm: AmPm) -> Time12 {
+        Time12 {
+            hour,
+            minute,
+            second,
+            am_pm,
+        }
+    }
+}
+
+/// Converts 24 hour time, midnight is 12 AM and noon is 12 PM
+/// ```
+/// use stm32f3_rtc::datetime::{AmPm, Time, Time12};
+/// let midnight: Time12 = Time::from(0, 15, 0).into();
+/// assert_eq!(midnight, Time12::from(12, 15, 0, AmPm::AM));
+/// let evening: Time12 = Time::from(18, 0, 0).into();
+/// assert_eq!(evening, Time12::from(6, 0, 0, AmPm::PM));
+/// ```
+impl From<Time> for Time12 {
+    fn from(time: Time) -> Self {
+        let am_pm = match time.hour < 12 {
+            true => AmPm::AM,
+            false => AmPm::PM,
+        };
+        let hour = match time.hour % 12 {
+            0 => 12,
+            hour => hour,
+        };
+        Time12::from(hour, time.minute, time.second, am_pm)
+    }
+}
+
+/// Converts 12 hour time into 24 hour time
+/// ```
+/// use stm32f3_rtc::datetime::{AmPm, Time, Time12};
+/// let midnight: Time = Time12::from(12, 15, 0, AmPm::AM).into();
+/// assert_eq!(midnight, Time::from(0, 15, 0));
+/// let noon: Time = Time12::from(12, 0, 0, AmPm::PM).into();
+/// assert_eq!(noon, Time::from(12, 0, 0));
+/// ```
+impl From<Time12> for Time {
+    fn from(time: Time12) -> Self {
+        let hour = match time.am_pm {
+            AmPm::AM => time.hour % 12,
+            AmPm::PM => time.hour % 12 + 12,
+        };
+        Time::from(hour, time.minute, time.second)
+    }
+}
+
+/// Keeps date and time together with fraction of second
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DateTime {
+    pub date: Date,
+    pub time: Time,
+    /// Nanoseconds of current second, resolution depends on RTC synchronous prescaler
+    pub nanosecond: u32,
+}
+
+impl DateTime {
+    /// Create a new DateTime struct from fallowing arguments,
+    /// (date, time, nanosecond)
+    pub fn from(date: Date, time: Time, nanosecond: u32) -> DateTime {
+        DateTime {
+            date,
+            time,
+            nanosecond,
+        }
+    }
+
+    /// Returns Julian Date, Julian day starts at noon
+    /// ```
+    /// use stm32f3_rtc::datetime::{Date, DateTime, JulianDate, Time};
+    /// let noon = DateTime::from(Date::from(1, 1, 2000), Time::from(12, 0, 0), 0);
+    /// assert_eq!(noon.julian_date(), JulianDate { day: 2_451_545, fraction: 0 });
+    /// let date_time = DateTime::from(Date::from(1, 1, 2000), Time::from(6, 0, 0), 0);
+    /// assert_eq!(date_time.julian_date().to_f64(), 2_451_544.75);
+    /// assert_eq!(DateTime::from_julian_date(&date_time.julian_date()), Some(date_time));
+    /// ```
+    pub fn julian_date(&self) -> JulianDate {
+        let nanoseconds = self.nanoseconds_of_day();
+        let jdn = self.date.julian_day_number();
+        match nanoseconds >= NANOSECONDS_PER_DAY / 2 {
+            true => JulianDate::from_nanoseconds(jdn, nanoseconds - NANOSECONDS_PER_DAY / 2),
+            false => JulianDate::from_nanoseconds(jdn - 1, nanoseconds + NANOSECONDS_PER_DAY / 2),
+        }
+    }
+
+    /// Create a new DateTime from Julian Date, returns None for dates before year 0
+    pub fn from_julian_date(julian_date: &JulianDate) -> Option<DateTime> {
+        let nanoseconds = julian_date.nanoseconds() + NANOSECONDS_PER_DAY / 2;
+        let jdn = julian_date.day + (nanoseconds / NANOSECONDS_PER_DAY) as i32;
+        Some(Self::from_nanoseconds_of_day(
+            Date::from_julian_day_number(jdn)?,
+            nanoseconds % NANOSECONDS_PER_DAY,
+        ))
+    }
+
+    /// Returns Modified Julian Date, modified Julian day starts at midnight
+    /// ```
+    /// use stm32f3_rtc::datetime::{Date, DateTime, Time};
+    /// let date_time = DateTime::from(Date::from(1, 1, 2000), Time::from(18, 0, 0), 0);
+    /// assert_eq!(date_time.modified_julian_date().to_f64(), 51_544.75);
+    /// ```
+    pub fn modified_julian_date(&self) -> JulianDate {
+        JulianDate::from_nanoseconds(self.date.modified_julian_day(), self.nanoseconds_of_day())
+    }
+
+    /// Create a new DateTime from Modified Julian Date, returns None for dates before year 0
+    pub fn from_modified_julian_date(julian_date: &JulianDate) -> Option<DateTime> {
+        Some(Self::from_nanoseconds_of_day(
+            Date::from_modified_julian_day(julian_date.day)?,
+            julian_date.nanoseconds(),
+        ))
+    }
+
+    /// Returns nanoseconds elapsed since midnight
+    fn nanoseconds_of_day(&self) -> u64 {
+        u64::from(self.time.to_seconds()) * 1_000_000_000
+            + u64::from(self.nanosecond.min(999_999_999))
+    }
+
+    /// Create date and time from nanoseconds of the day, full day moves it to the next day
+    fn from_nanoseconds_of_day(date: Date, nanoseconds: u64) -> DateTime {
+        let (date, nanoseconds) = match nanoseconds >= NANOSECONDS_PER_DAY {
+            true => (date.next_day(), nanoseconds - NANOSECONDS_PER_DAY),
+            false => (date, nanoseconds),
+        };
+        let seconds = (nanoseconds / 1_000_000_000) as u32;
+        let time = Time::from(
+            (seconds / 3600) as u8,
+            (seconds / 60 % 60) as u8,
+            (seconds % 60) as u8,
+        );
+        DateTime::from(date, time, (nanoseconds % 1_000_000_000) as u32)
+    }
+
+    /// Returns date and time moved by given amount of seconds, date is changed day by day,
+    /// so it is meant for offsets of few days at most
+    pub(crate) fn add_seconds(&self, seconds: i32) -> DateTime {
+        let seconds = self.time.to_seconds() as i32 + seconds;
+        let mut date = self.date;
+        for _ in 0..seconds.div_euclid(86_400) {
+            date = date.next_day();
+        }
+        for _ in seconds.div_euclid(86_400)..0 {
+            date = date.previous_day();
+        }
+        let seconds = seconds.rem_euclid(86_400) as u32;
+        let time = Time::from(
+            (seconds / 3600) as u8,
+            (seconds / 60 % 60) as u8,
+            (seconds % 60) as u8,
+        );
+        DateTime::from(date, time, self.nanosecond)
+    }
+}
+
+/// Formats date as ISO 8601 calendar date (YYYY-MM-DD)
+/// ```
+/// use stm32f3_rtc::datetime::Date;
+/// assert_eq!(format!("{}", Date::from(1, 6, 2024)), "2024-06-01");
+/// ```
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Formats time as ISO 8601 time (hh:mm:ss)
+/// ```
+/// use stm32f3_rtc::datetime::Time;
+/// assert_eq!(format!("{}", Time::from(9, 5, 0)), "09:05:00");
+/// ```
+impl fmt::Display for Time {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:02}:{:02}:{:02}", self.hour, self.minute, self.second)
+    }
+}
+
+/// Formats 12 hour time (hh:mm:ss AM)
+/// ```
+/// use stm32f3_rtc::datetime::{AmPm, Time12};
+/// assert_eq!(format!("{}", Time12::from(7, 5, 0, AmPm::PM)), "07:05:00 PM");
+/// ```
+impl fmt::Display for Time12 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let am_pm = match self.am_pm {
+            AmPm::AM => "AM",
+            AmPm::PM => "PM",
+        };
+        write!(
+            f,
+            "{:02}:{:02}:{:02} {}",
+            self.hour, self.minute, self.second, am_pm
+        )
+    }
+}
+
+/// Formats date and time as ISO 8601 date-time (YYYY-MM-DDThh:mm:ss), milliseconds
+/// are added when fraction of second is present
+/// ```
+/// use stm32f3_rtc::datetime::{Date, DateTime, Time};
+/// let date_time = DateTime::from(Date::from(1, 6, 2024), Time::from(12, 30, 0), 0);
+/// assert_eq!(format!("{}", date_time), "2024-06-01T12:30:00");
+/// let date_time = DateTime::from(Date::from(1, 6, 2024), Time::from(12, 30, 0), 5_000_000);
+/// assert_eq!(format!("{}", date_time), "2024-06-01T12:30:00.005");
+/// ```
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}T{}", self.date, self.time)?;
+        match self.nanosecond / 1_000_000 {
+            0 => Ok(()),
+            millisecond => write!(f, ".{:03}", millisecond),
+        }
+    }
 }
 
 /// Single BCD encoded value
+#[derive(Debug)]
 pub struct Bcd<T> {
     pub(crate) tens: T,
     pub(crate) units: T,
@@ -67,6 +727,17 @@ impl Bcd<u8> {
     }
 }
 
+/// Formats BCD value as its two decimal digits
+/// ```
+/// use stm32f3_rtc::datetime::Bcd;
+/// assert_eq!(format!("{}", Bcd::set(7)), "07");
+/// ```
+impl fmt::Display for Bcd<u8> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.tens, self.units)
+    }
+}
+
 /// Keeps BCD time
 pub(crate) struct BcdTime {
     pub(crate) hour: Bcd<u8>,
@@ -108,30 +779,25 @@ pub(crate) struct BcdDate {
 
 /// API for easy date access to BCD date converted into NaiveDate
 impl BcdDate {
-    /// Returns date converted from BCD to NaiveTime
+    /// Returns date converted from BCD to Date, year keeps only two digits, full year is
+    /// added by [century](crate::century) tracker
     pub(crate) fn date(self) -> Date {
-        let century: u32 = 2000;
         Date {
             day: self.d.get(),
             month: self.m.get(),
-            year: u32::from(self.y.get()) + century,
+            year: u32::from(self.y.get()),
         }
     }
 }
 
 /// API for easy create BCD date from NaiveDate
 impl From<Date> for BcdDate {
-    /// Create BCD encoded date from Date struct
+    /// Create BCD encoded date from Date struct, RTC_DR keeps only last two digits of the year
     fn from(date: Date) -> Self {
-        // Because of RTC_DR limitation we cannot use dates earlier than 2000
-        let year = match date.year < 2000 || date.year > 2154 {
-            true => 2000,
-            false => date.year,
-        };
         Self {
             d: Self::bcd_encode(date.day),
             m: Self::bcd_encode(date.month),
-            y: Self::bcd_encode((year - 2000) as u8),
+            y: Self::bcd_encode((date.year % 100) as u8),
         }
     }
 }
@@ -160,3 +826,138 @@ trait BcdConvert {
         Bcd { tens, units }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Date, Weekday};
+
+    /// Date, weekday, day of year, ISO week and Julian Day Number
+    type Reference = (Date, Weekday, u16, (u32, u8), i32);
+
+    /// Reference dates checked against other calendar implementations
+    #[rustfmt::skip]
+    const REFERENCE: [Reference; 17] = [
+        (Date::from(1, 1, 1900), Weekday::Monday, 1, (1900, 1), 2_415_021),
+        (Date::from(1, 1, 1970), Weekday::Thursday, 1, (1970, 1), 2_440_588),
+        (Date::from(1, 1, 2000), Weekday::Saturday, 1, (1999, 52), 2_451_545),
+        (Date::from(29, 2, 2000), Weekday::Tuesday, 60, (2000, 9), 2_451_604),
+        (Date::from(31, 12, 2000), Weekday::Sunday, 366, (2000, 52), 2_451_910),
+        (Date::from(31, 12, 2004), Weekday::Friday, 366, (2004, 53), 2_453_371),
+        (Date::from(29, 12, 2008), Weekday::Monday, 364, (2009, 1), 2_454_830),
+        (Date::from(3, 1, 2010), Weekday::Sunday, 3, (2009, 53), 2_455_200),
+        (Date::from(1, 3, 2020), Weekday::Sunday, 61, (2020, 9), 2_458_910),
+        (Date::from(1, 1, 2021), Weekday::Friday, 1, (2020, 53), 2_459_216),
+        (Date::from(1, 3, 2023), Weekday::Wednesday, 60, (2023, 9), 2_460_005),
+        (Date::from(29, 2, 2024), Weekday::Thursday, 60, (2024, 9), 2_460_370),
+        (Date::from(1, 6, 2024), Weekday::Saturday, 153, (2024, 22), 2_460_463),
+        (Date::from(30, 12, 2024), Weekday::Monday, 365, (2025, 1), 2_460_675),
+        (Date::from(19, 1, 2038), Weekday::Tuesday, 19, (2038, 3), 2_465_443),
+        (Date::from(31, 12, 2099), Weekday::Thursday, 365, (2099, 53), 2_488_069),
+        (Date::from(1, 3, 2100), Weekday::Monday, 60, (2100, 9), 2_488_129),
+    ];
+
+    #[test]
+    fn reference_dates() {
+        for &(date, weekday, day_of_year, iso_week, jdn) in REFERENCE.iter() {
+            assert_eq!(date.weekday(), weekday, "{}", date);
+            assert_eq!(date.day_of_year(), day_of_year, "{}", date);
+            assert_eq!(date.iso_week(), iso_week, "{}", date);
+            assert_eq!(date.julian_day_number(), jdn, "{}", date);
+            assert_eq!(Date::from_julian_day_number(jdn), Some(date));
+        }
+    }
+
+    #[test]
+    fn leap_years() {
+        for &year in [1904, 2000, 2024, 2096, 2400].iter() {
+            assert!(Date::is_leap_year(year), "{}", year);
+        }
+        for &year in [1900, 2023, 2100, 2200, 2300].iter() {
+            assert!(!Date::is_leap_year(year), "{}", year);
+        }
+    }
+
+    /// ISO week found from Thursday of the same week, that decides the week-based year
+    fn iso_week_from_thursday(jdn: i32, weekday: Weekday) -> (u32, u8) {
+        let thursday = jdn - i32::from(weekday.number_from_monday()) + 4;
+        let thursday = Date::from_julian_day_number(thursday).unwrap();
+        (thursday.year, ((thursday.day_of_year() - 1) / 7 + 1) as u8)
+    }
+
+    #[test]
+    fn every_day_of_2000_2099() {
+        let mut previous = Date::from(31, 12, 1999);
+        let mut jdn = previous.julian_day_number();
+        for year in 2000..=2099 {
+            let mut day_of_year = 0;
+            let mut thursdays = 0;
+            for month in 1..=12 {
+                // Occurrences of each weekday in the month and the last date of each of them
+                let mut occurrences = [0u8; 7];
+                let mut last = [Date::from(0, 0, 0); 7];
+                for day in 1..=Date::days_in_month(year, month) {
+                    let date = Date::from(day, month, year);
+                    day_of_year += 1;
+                    jdn += 1;
+                    assert_eq!(previous.next_day(), date);
+                    assert_eq!(date.previous_day(), previous);
+                    assert_eq!(date.day_of_year(), day_of_year);
+                    assert_eq!(date.julian_day_number(), jdn);
+                    assert_eq!(Date::from_julian_day_number(jdn), Some(date));
+                    // JDN is counted from Monday
+                    let weekday = date.weekday();
+                    assert_eq!(weekday.number_from_monday() as i32, jdn % 7 + 1);
+                    assert_eq!(date.iso_week(), iso_week_from_thursday(jdn, weekday));
+                    let index = weekday.number_from_monday() as usize - 1;
+                    occurrences[index] += 1;
+                    last[index] = date;
+                    let nth = Date::nth_weekday(year, month, occurrences[index], weekday);
+                    assert_eq!(nth, Some(date));
+                    thursdays += (weekday == Weekday::Thursday) as u8;
+                    previous = date;
+                }
+                for (index, &count) in occurrences.iter().enumerate() {
+                    let weekday = Weekday::from_days_from_sunday((index as u8 + 1) % 7);
+                    assert_eq!(Date::nth_weekday(year, month, count + 1, weekday), None);
+                    assert_eq!(Date::last_weekday(year, month, weekday), last[index]);
+                }
+            }
+            assert_eq!(day_of_year, 365 + Date::is_leap_year(year) as u16);
+            // ISO year has a week for every Thursday of the calendar year
+            assert_eq!(Date::iso_weeks_in_year(year), thursdays, "{}", year);
+        }
+    }
+
+    #[test]
+    fn iso_week_of_year_0() {
+        // 1 January of year 0 is Saturday, so it belongs to the last week of year -1
+        assert_eq!(Date::from(1, 1, 0).iso_week(), (0, 0));
+        assert_eq!(Date::from(2, 1, 0).iso_week(), (0, 0));
+        assert_eq!(Date::from(3, 1, 0).iso_week(), (0, 1));
+    }
+
+    #[test]
+    fn nth_and_last_weekday() {
+        assert_eq!(
+            Date::nth_weekday(2024, 3, 5, Weekday::Sunday),
+            Some(Date::from(31, 3, 2024))
+        );
+        assert_eq!(
+            Date::nth_weekday(2024, 3, 1, Weekday::Friday),
+            Some(Date::from(1, 3, 2024))
+        );
+        assert_eq!(Date::nth_weekday(2024, 2, 5, Weekday::Friday), None);
+        assert_eq!(
+            Date::last_weekday(2024, 3, Weekday::Sunday),
+            Date::from(31, 3, 2024)
+        );
+        assert_eq!(
+            Date::last_weekday(2023, 10, Weekday::Sunday),
+            Date::from(29, 10, 2023)
+        );
+        assert_eq!(
+            Date::last_weekday(2099, 12, Weekday::Thursday),
+            Date::from(31, 12, 2099)
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! [defmt](https://docs.rs/defmt) formatting for [datetime](crate::datetime) types, with the
+//! same ISO 8601 style output as their **Display** implementation.
+//!
+//! With `defmt-timestamp` feature crate also provides defmt global timestamp, so every
+//! log message is stamped with RTC date and time with milliseconds:
+//! `2024-06-01T12:30:00.125`
+//!
+//! ## Usage:
+//! ```
+//! use stm32f3_rtc::datetime::TimeAccess;
+//! ...
+//! defmt::info!("Measurement taken at {}", rtc.time());
+//! ```
+#[cfg(feature = "defmt-timestamp")]
+use clock::RtcClock;
+use datetime::{Bcd, Date, DateTime, Time};
+use defmt::{write, Format, Formatter};
+
+impl Format for Date {
+    fn format(&self, f: Formatter) {
+        write!(
+            f,
+            "{=u32:04}-{=u8:02}-{=u8:02}",
+            self.year, self.month, self.day
+        )
+    }
+}
+
+impl Format for Time {
+    fn format(&self, f: Formatter) {
+        write!(
+            f,
+            "{=u8:02}:{=u8:02}:{=u8:02}",
+            self.hour, self.minute, self.second
+        )
+    }
+}
+
+impl Format for DateTime {
+    fn format(&self, f: Formatter) {
+        write!(f, "{}T{}", self.date, self.time);
+        match self.nanosecond / 1_000_000 {
+            0 => {}
+            millisecond => write!(f, ".{=u32:03}", millisecond),
+        }
+    }
+}
+
+impl Format for Bcd<u8> {
+    fn format(&self, f: Formatter) {
+        write!(f, "{=u8}{=u8}", self.tens, self.units)
+    }
+}
+
+/// Date and time used as defmt global timestamp, milliseconds are always shown
+#[cfg(feature = "defmt-timestamp")]
+struct Timestamp(DateTime);
+
+#[cfg(feature = "defmt-timestamp")]
+impl Format for Timestamp {
+    fn format(&self, f: Formatter) {
+        write!(
+            f,
+            "{}T{}.{=u32:03}",
+            self.0.date,
+            self.0.time,
+            self.0.nanosecond / 1_000_000
+        )
+    }
+}
+
+#[cfg(feature = "defmt-timestamp")]
+fn timestamp() -> Timestamp {
+    Timestamp(RtcClock::new().date_time())
+}
+
+#[cfg(feature = "defmt-timestamp")]
+defmt::timestamp!("{}", timestamp());
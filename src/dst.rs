@@ -0,0 +1,291 @@
+//! Daylight saving time rules, that let RTC follow local time automatically.
+//!
+//! RTC keeps local wall clock time and the RTC backup bit remembers whether summer time
+//! is applied, so [Rtc::update_daylight_saving] moves the clock exactly once per transition,
+//! also after reset. Call it on every wake up (or set wake up for
+//! [DstRules::next_transition]). Backup bit is cleared by **start_clock()**, so after local
+//! time is set, [Rtc::sync_daylight_saving] has to mark it without moving the clock.
+//!
+//! ## Usage:
+//! ```
+//! use stm32f3_rtc::dst::DstRules;
+//! use stm32f3_rtc::rtc::Rtc;
+//! use stm32f3xx_hal::pac;
+//!
+//! let mut peripheral = pac::Peripherals::take().unwrap();
+//! let mut rtc = Rtc::new(peripheral.RTC);
+//! rtc.start_clock(&mut peripheral.PWR, &mut peripheral.RCC);
+//! // Central European Time (UTC+1)
+//! let rules = DstRules::eu(1);
+//! // Backup bit is cleared by start_clock(), it is taken from the rules for local time
+//! rtc.sync_daylight_saving(&rules);
+//! loop {
+//!     rtc.update_daylight_saving(&rules);
+//!     // ... sleep until next wake up
+//! }
+//! ```
+use datetime::{Date, DateTime, Time, Weekday};
+use rtc::Rtc;
+
+/// Week of the month in which transition happens
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Week {
+    First,
+    Second,
+    Third,
+    Fourth,
+    Last,
+}
+
+/// Change of the clock made by daylight saving
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Transition {
+    /// Clock is moved one hour forward
+    ToSummerTime,
+    /// Clock is moved one hour back
+    ToWinterTime,
+}
+
+/// Single transition rule, e.g. "last Sunday of March at 02:00"
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DstRule {
+    pub week: Week,
+    pub weekday: Weekday,
+    pub month: u8,
+    /// Local wall clock time just before the transition
+    pub time: Time,
+}
+
+impl DstRule {
+    /// Create a new DstRule struct from following arguments
+    /// (week, weekday, month, time)
+    pub fn from(week: Week, weekday: Weekday, month: u8, time: Time) -> DstRule {
+        DstRule {
+            week,
+            weekday,
+            month,
+            time,
+        }
+    }
+
+    /// Returns date of the transition in given year
+    /// ```
+    /// use stm32f3_rtc::datetime::{Date, Time, Weekday};
+    /// use stm32f3_rtc::dst::{DstRule, Week};
+    /// let rule = DstRule::from(Week::Last, Weekday::Sunday, 3, Time::from(2, 0, 0));
+    /// assert_eq!(rule.date(2024), Date::from(31, 3, 2024));
+    /// let rule = DstRule::from(Week::Second, Weekday::Sunday, 3, Time::from(2, 0, 0));
+    /// assert_eq!(rule.date(2024), Date::from(10, 3, 2024));
+    /// ```
+    pub fn date(&self, year: u32) -> Date {
+        let n = match self.week {
+            Week::First => 1,
+            Week::Second => 2,
+            Week::Third => 3,
+            Week::Fourth => 4,
+            Week::Last => return Date::last_weekday(year, self.month, self.weekday),
+        };
+        Date::nth_weekday(year, self.month, n, self.weekday)
+            .unwrap_or_else(|| Date::last_weekday(year, self.month, self.weekday))
+    }
+
+    /// Returns moment of the transition as seconds since beginning of given year
+    fn seconds_of_year(&self, year: u32) -> i32 {
+        seconds_of_year(&self.date(year), &self.time)
+    }
+}
+
+/// Pair of rules, that start and end summer time
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DstRules {
+    /// Start of summer time, time is given in local winter (standard) time
+    pub start: DstRule,
+    /// End of summer time, time is given in local summer time
+    pub end: DstRule,
+}
+
+impl DstRules {
+    /// Create a new DstRules struct from following arguments
+    /// (start, end)
+    pub fn from(start: DstRule, end: DstRule) -> DstRules {
+        DstRules { start, end }
+    }
+
+    /// European Union rules: from last Sunday of March to last Sunday of October,
+    /// both at 01:00 UTC. Takes standard time offset from UTC in hours (e.g. 1 for CET)
+    pub fn eu(utc_offset: i8) -> DstRules {
+        let hour = (1 + i16::from(utc_offset)).rem_euclid(24) as u8;
+        DstRules {
+            start: DstRule::from(Week::Last, Weekday::Sunday, 3, Time::from(hour, 0, 0)),
+            end: DstRule::from(
+                Week::Last,
+                Weekday::Sunday,
+                10,
+                Time::from((hour + 1) % 24, 0, 0),
+            ),
+        }
+    }
+
+    /// United States rules: from second Sunday of March to first Sunday of November,
+    /// both at 02:00 local time
+    pub fn us() -> DstRules {
+        DstRules {
+            start: DstRule::from(Week::Second, Weekday::Sunday, 3, Time::from(2, 0, 0)),
+            end: DstRule::from(Week::First, Weekday::Sunday, 11, Time::from(2, 0, 0)),
+        }
+    }
+
+    /// Returns true if summer time should be applied at given moment. Clock state is needed,
+    /// because the same wall clock hour repeats after summer time ends
+    /// ```
+    /// use stm32f3_rtc::datetime::{Date, Time};
+    /// use stm32f3_rtc::dst::DstRules;
+    /// let rules = DstRules::eu(1);
+    /// assert!(rules.is_summer_time(&Date::from(1, 6, 2024), &Time::from(12, 0, 0), false));
+    /// assert!(!rules.is_summer_time(&Date::from(27, 10, 2024), &Time::from(3, 0, 0), true));
+    /// assert!(rules.is_summer_time(&Date::from(27, 10, 2024), &Time::from(2, 59, 59), true));
+    /// ```
+    pub fn is_summer_time(&self, date: &Date, time: &Time, summer_time: bool) -> bool {
+        let now = seconds_of_year(date, time);
+        let mut start = self.start.seconds_of_year(date.year);
+        let mut end = self.end.seconds_of_year(date.year);
+        // Both moments are compared in the time that clock shows now
+        match summer_time {
+            true => start += 3600,
+            false => end -= 3600,
+        }
+        match start < end {
+            true => now >= start && now < end,
+            // Southern hemisphere, summer time lasts over new year
+            false => now >= start || now < end,
+        }
+    }
+
+    /// Returns wall clock date and time of the next transition together with its kind
+    /// ```
+    /// use stm32f3_rtc::datetime::{Date, Time};
+    /// use stm32f3_rtc::dst::{DstRules, Transition};
+    /// let rules = DstRules::us();
+    /// let (at, transition) = rules.next_transition(&Date::from(1, 12, 2024), &Time::from(0, 0, 0), false);
+    /// assert_eq!((at.date, at.time), (Date::from(9, 3, 2025), Time::from(2, 0, 0)));
+    /// assert_eq!(transition, Transition::ToSummerTime);
+    /// ```
+    pub fn next_transition(
+        &self,
+        date: &Date,
+        time: &Time,
+        summer_time: bool,
+    ) -> (DateTime, Transition) {
+        let (rule, transition) = match summer_time {
+            true => (&self.end, Transition::ToWinterTime),
+            false => (&self.start, Transition::ToSummerTime),
+        };
+        let year = match seconds_of_year(date, time) < rule.seconds_of_year(date.year) {
+            true => date.year,
+            false => date.year + 1,
+        };
+        (DateTime::from(rule.date(year), rule.time, 0), transition)
+    }
+}
+
+/// Returns seconds elapsed since beginning of the year
+fn seconds_of_year(date: &Date, time: &Time) -> i32 {
+    (i32::from(date.day_of_year()) - 1) * 86_400 + time.to_seconds() as i32
+}
+
+/// Returns transition, that has to be applied to the clock showing given date and time
+fn required_transition(
+    rules: &DstRules,
+    date: &Date,
+    time: &Time,
+    summer_time: bool,
+) -> Option<Transition> {
+    match (summer_time, rules.is_summer_time(date, time, summer_time)) {
+        (false, true) => Some(Transition::ToSummerTime),
+        (true, false) => Some(Transition::ToWinterTime),
+        _ => None,
+    }
+}
+
+impl Rtc {
+    /// Applies summer or winter time when current date and time require it according to
+    /// given rules. RTC backup bit keeps the state, so every transition is applied once.
+    /// Returns applied transition.
+    ///
+    /// **Note:** Winter time cannot be applied at hour 0, it is applied by the next call
+    pub fn update_daylight_saving(&mut self, rules: &DstRules) -> Option<Transition> {
+        let date_time = self.date_time();
+        let summer_time = self.is_summer_time();
+        match required_transition(rules, &date_time.date, &date_time.time, summer_time) {
+            Some(Transition::ToSummerTime) if self.apply_summer_time() => {
+                Some(Transition::ToSummerTime)
+            }
+            Some(Transition::ToWinterTime) if self.apply_winter_time() => {
+                Some(Transition::ToWinterTime)
+            }
+            _ => None,
+        }
+    }
+
+    /// Marks summer time in RTC backup bit according to given rules, without moving the clock.
+    /// It has to be called after local time is set (e.g. after **start_clock()**), otherwise
+    /// [Rtc::update_daylight_saving] moves clock set in summer time once more. Repeated hour
+    /// after the end of summer time is taken as winter time. Returns marked state.
+    pub fn sync_daylight_saving(&mut self, rules: &DstRules) -> bool {
+        let date_time = self.date_time();
+        let summer_time = rules.is_summer_time(&date_time.date, &date_time.time, false);
+        self.mark_summer_time(summer_time);
+        summer_time
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{required_transition, DstRules, Transition};
+    use datetime::{Date, Time};
+
+    #[test]
+    fn clock_set_after_start_is_not_moved_once_synced() {
+        let rules = DstRules::eu(1);
+        #[rustfmt::skip]
+        let moments = [
+            (Date::from(15, 7, 2024), Time::from(12, 0, 0), true),
+            (Date::from(15, 1, 2024), Time::from(12, 0, 0), false),
+            // Repeated hour after the end of summer time
+            (Date::from(27, 10, 2024), Time::from(2, 30, 0), false),
+            (Date::from(17, 3, 2024), Time::from(12, 0, 0), false),
+        ];
+        for &(date, time, summer_time) in moments.iter() {
+            // Backup bit is cleared by start_clock(), sync_daylight_saving marks it from rules
+            let marked = rules.is_summer_time(&date, &time, false);
+            assert_eq!(marked, summer_time, "{} {}", date, time);
+            assert_eq!(required_transition(&rules, &date, &time, marked), None);
+        }
+        // Without marking, clock set in summer time would be moved forward once more
+        let (date, time) = (Date::from(15, 7, 2024), Time::from(12, 0, 0));
+        assert_eq!(
+            required_transition(&rules, &date, &time, false),
+            Some(Transition::ToSummerTime)
+        );
+    }
+
+    #[test]
+    fn transitions_are_required_once() {
+        let rules = DstRules::eu(1);
+        let start = Date::from(31, 3, 2024);
+        let end = Date::from(27, 10, 2024);
+        #[rustfmt::skip]
+        let moments = [
+            (start, Time::from(1, 59, 59), false, None),
+            (start, Time::from(2, 0, 0), false, Some(Transition::ToSummerTime)),
+            (start, Time::from(3, 0, 0), true, None),
+            (end, Time::from(2, 59, 59), true, None),
+            (end, Time::from(3, 0, 0), true, Some(Transition::ToWinterTime)),
+            (end, Time::from(2, 0, 0), false, None),
+        ];
+        for &(date, time, summer_time, transition) in moments.iter() {
+            let required = required_transition(&rules, &date, &time, summer_time);
+            assert_eq!(required, transition, "{} {}", date, time);
+        }
+    }
+}
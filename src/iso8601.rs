@@ -0,0 +1,464 @@
+//! ISO 8601 / RFC 3339 parsing and formatting for [datetime](crate::datetime) types, without
+//! allocation.
+//!
+//! Supported formats:
+//! - Date: `YYYY-MM-DD`
+//! - Time: `hh:mm:ss`
+//! - DateTime: `YYYY-MM-DDThh:mm:ss[.fffffffff][Z|±hh:mm]`
+//!
+//! Fraction of second can have 1 - 9 digits and can be separated with `.` or `,`.
+//! Date and time can be separated with `T`, `t` or space (allowed by RFC 3339).
+//! Every field is validated, so e.g. `2023-02-29` or `24:00:00` are rejected.
+//!
+//! ## Usage:
+//! ```
+//! use stm32f3_rtc::datetime::{Date, DateTime, Time};
+//! use stm32f3_rtc::iso8601::{parse_with_offset, SliceWriter};
+//!
+//! // Value received over UART
+//! let date_time = DateTime::parse_iso8601("2024-06-01T12:30:00.25Z").unwrap();
+//! assert_eq!(date_time.date, Date::from(1, 6, 2024));
+//! assert_eq!(date_time.time, Time::from(12, 30, 0));
+//! assert_eq!(date_time.nanosecond, 250_000_000);
+//!
+//! let (local, offset) = parse_with_offset("2024-06-01T14:30:00+02:00").unwrap();
+//! assert_eq!((local.time, offset), (Time::from(14, 30, 0), Some(120)));
+//!
+//! let mut buffer = [0u8; 32];
+//! let mut writer = SliceWriter::new(&mut buffer);
+//! date_time.write_rfc3339(&mut writer, 3, 0).unwrap();
+//! assert_eq!(writer.as_str(), "2024-06-01T12:30:00.250Z");
+//! ```
+use core::fmt;
+use core::str;
+use datetime::{Date, DateTime, Time};
+
+/// Maximal length of text written by [DateTime::write_rfc3339]
+/// (`YYYY-MM-DDThh:mm:ss.fffffffff+hh:mm`)
+pub const MAX_LENGTH: usize = 35;
+
+/// Errors returned by ISO 8601 parsing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    /// Text ends before all fields are read
+    TooShort,
+    /// Text has characters after the last field
+    TrailingCharacters,
+    /// Character at given byte position is not allowed there
+    InvalidCharacter(usize),
+    /// Field value is out of its range (e.g. month 13, 31 April or hour 24)
+    OutOfRange,
+    /// Text has UTC offset other than zero, where only UTC or local time is accepted.
+    /// Please use [parse_with_offset] to read it.
+    UnexpectedOffset,
+    /// Text has no UTC offset, where it is required (RFC 3339)
+    MissingOffset,
+}
+
+/// Reads fields one after another, keeping position for error reporting
+struct Parser<'a> {
+    text: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        Parser {
+            text: text.as_bytes(),
+            position: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.get(self.position).cloned()
+    }
+
+    /// Reads one of expected characters
+    fn expect(&mut self, expected: &[u8]) -> Result<u8, ParseError> {
+        match self.peek() {
+            None => Err(ParseError::TooShort),
+            Some(byte) if expected.contains(&byte) => {
+                self.position += 1;
+                Ok(byte)
+            }
+            Some(_) => Err(ParseError::InvalidCharacter(self.position)),
+        }
+    }
+
+    /// Reads exactly `count` decimal digits
+    fn number(&mut self, count: usize) -> Result<u32, ParseError> {
+        let mut value = 0;
+        for _ in 0..count {
+            let digit = self.expect(b"0123456789")?;
+            value = value * 10 + u32::from(digit - b'0');
+        }
+        Ok(value)
+    }
+
+    /// Reads number with given amount of digits and checks its range
+    fn field(&mut self, count: usize, min: u32, max: u32) -> Result<u8, ParseError> {
+        match self.number(count)? {
+            value if value >= min && value <= max => Ok(value as u8),
+            _ => Err(ParseError::OutOfRange),
+        }
+    }
+
+    fn date(&mut self) -> Result<Date, ParseError> {
+        let year = self.number(4)?;
+        self.expect(b"-")?;
+        let month = self.field(2, 1, 12)?;
+        self.expect(b"-")?;
+        let day = self.field(2, 1, u32::from(Date::days_in_month(year, month)))?;
+        Ok(Date::from(day, month, year))
+    }
+
+    fn time(&mut self) -> Result<Time, ParseError> {
+        let hour = self.field(2, 0, 23)?;
+        self.expect(b":")?;
+        let minute = self.field(2, 0, 59)?;
+        self.expect(b":")?;
+        // Leap second (60) cannot be kept by RTC
+        let second = self.field(2, 0, 59)?;
+        Ok(Time::from(hour, minute, second))
+    }
+
+    /// Reads optional fraction of second, returns it as nanoseconds
+    fn fraction(&mut self) -> Result<u32, ParseError> {
+        if self.peek() != Some(b'.') && self.peek() != Some(b',') {
+            return Ok(0);
+        }
+        self.position += 1;
+        let mut digits = 0;
+        let mut nanosecond = self.number(1)?;
+        while let Some(b'0'..=b'9') = self.peek() {
+            if digits == 8 {
+                return Err(ParseError::InvalidCharacter(self.position));
+            }
+            nanosecond = nanosecond * 10 + self.number(1)?;
+            digits += 1;
+        }
+        Ok(nanosecond * 10u32.pow(8 - digits))
+    }
+
+    /// Reads optional UTC offset, returns it in minutes
+    fn offset(&mut self) -> Result<Option<i16>, ParseError> {
+        let sign = match self.peek() {
+            None => return Ok(None),
+            Some(b'Z') | Some(b'z') => {
+                self.position += 1;
+                return Ok(Some(0));
+            }
+            Some(_) => self.expect(b"+-")?,
+        };
+        let hour = self.field(2, 0, 23)?;
+        self.expect(b":")?;
+        let minute = self.field(2, 0, 59)?;
+        let offset = i16::from(hour) * 60 + i16::from(minute);
+        match sign {
+            b'-' => Ok(Some(-offset)),
+            _ => Ok(Some(offset)),
+        }
+    }
+
+    fn date_time(&mut self) -> Result<DateTime, ParseError> {
+        let date = self.date()?;
+        self.expect(b"Tt ")?;
+        let time = self.time()?;
+        Ok(DateTime::from(date, time, self.fraction()?))
+    }
+
+    /// Checks that whole text was read
+    fn finish<T>(&self, value: T) -> Result<T, ParseError> {
+        match self.position == self.text.len() {
+            true => Ok(value),
+            false => Err(ParseError::TrailingCharacters),
+        }
+    }
+}
+
+/// Parses date and time with optional UTC offset. Returns date and time exactly as written
+/// in the text (local time of the sender) together with offset in minutes, `None` when
+/// offset is not present.
+/// ```
+/// use stm32f3_rtc::iso8601::{parse_with_offset, ParseError};
+/// let (date_time, offset) = parse_with_offset("2024-06-01T07:30:00-05:00").unwrap();
+/// assert_eq!((date_time.time.hour, offset), (7, Some(-300)));
+/// assert_eq!(parse_with_offset("2024-06-01T07:30:00").unwrap().1, None);
+/// assert_eq!(
+///     parse_with_offset("2024-06-01T07:30:00+5:00"),
+///     Err(ParseError::InvalidCharacter(21))
+/// );
+/// ```
+pub fn parse_with_offset(text: &str) -> Result<(DateTime, Option<i16>), ParseError> {
+    let mut parser = Parser::new(text);
+    let date_time = parser.date_time()?;
+    let offset = parser.offset()?;
+    parser.finish((date_time, offset))
+}
+
+impl Date {
+    /// Parses date in ISO 8601 extended format (YYYY-MM-DD)
+    /// ```
+    /// use stm32f3_rtc::datetime::Date;
+    /// use stm32f3_rtc::iso8601::ParseError;
+    /// assert_eq!(Date::parse_iso8601("2024-02-29"), Ok(Date::from(29, 2, 2024)));
+    /// assert_eq!(Date::parse_iso8601("2023-02-29"), Err(ParseError::OutOfRange));
+    /// assert_eq!(Date::parse_iso8601("2024-6-01"), Err(ParseError::InvalidCharacter(6)));
+    /// ```
+    pub fn parse_iso8601(text: &str) -> Result<Date, ParseError> {
+        let mut parser = Parser::new(text);
+        let date = parser.date()?;
+        parser.finish(date)
+    }
+
+    /// Writes date in ISO 8601 extended format (YYYY-MM-DD)
+    pub fn write_iso8601<W: fmt::Write>(&self, writer: &mut W) -> fmt::Result {
+        write!(writer, "{}", self)
+    }
+}
+
+impl Time {
+    /// Parses time in ISO 8601 extended format (hh:mm:ss)
+    /// ```
+    /// use stm32f3_rtc::datetime::Time;
+    /// use stm32f3_rtc::iso8601::ParseError;
+    /// assert_eq!(Time::parse_iso8601("23:59:59"), Ok(Time::from(23, 59, 59)));
+    /// assert_eq!(Time::parse_iso8601("24:00:00"), Err(ParseError::OutOfRange));
+    /// assert_eq!(Time::parse_iso8601("12:30"), Err(ParseError::TooShort));
+    /// ```
+    pub fn parse_iso8601(text: &str) -> Result<Time, ParseError> {
+        let mut parser = Parser::new(text);
+        let time = parser.time()?;
+        parser.finish(time)
+    }
+
+    /// Writes time in ISO 8601 extended format (hh:mm:ss)
+    pub fn write_iso8601<W: fmt::Write>(&self, writer: &mut W) -> fmt::Result {
+        write!(writer, "{}", self)
+    }
+}
+
+impl DateTime {
+    /// Parses date and time in ISO 8601 / RFC 3339 format. Offset is optional, but it has to
+    /// be zero (`Z` or `+00:00`), so returned value is always UTC or local time
+    /// ```
+    /// use stm32f3_rtc::datetime::DateTime;
+    /// use stm32f3_rtc::iso8601::ParseError;
+    /// let date_time = DateTime::parse_iso8601("2024-06-01 12:30:00,5").unwrap();
+    /// assert_eq!(date_time.nanosecond, 500_000_000);
+    /// assert_eq!(
+    ///     DateTime::parse_iso8601("2024-06-01T12:30:00+01:00"),
+    ///     Err(ParseError::UnexpectedOffset)
+    /// );
+    /// ```
+    pub fn parse_iso8601(text: &str) -> Result<DateTime, ParseError> {
+        match parse_with_offset(text)? {
+            (date_time, None) | (date_time, Some(0)) => Ok(date_time),
+            _ => Err(ParseError::UnexpectedOffset),
+        }
+    }
+
+    /// Writes date and time in ISO 8601 format (YYYY-MM-DDThh:mm:ss[.fff]) without offset,
+    /// fraction of second is written with given amount of digits (0 - 9)
+    /// ```
+    /// use stm32f3_rtc::datetime::{Date, DateTime, Time};
+    /// let date_time = DateTime::from(Date::from(1, 6, 2024), Time::from(12, 30, 0), 5_000);
+    /// let mut text = String::new();
+    /// date_time.write_iso8601(&mut text, 6).unwrap();
+    /// assert_eq!(text, "2024-06-01T12:30:00.000005");
+    /// ```
+    pub fn write_iso8601<W: fmt::Write>(&self, writer: &mut W, fraction_digits: u8) -> fmt::Result {
+        write!(writer, "{}T{}", self.date, self.time)?;
+        let digits = u32::from(fraction_digits.min(9));
+        match digits {
+            0 => Ok(()),
+            _ => write!(
+                writer,
+                ".{:0width$}",
+                self.nanosecond / 10u32.pow(9 - digits),
+                width = digits as usize
+            ),
+        }
+    }
+
+    /// Writes date and time in RFC 3339 format with UTC offset given in minutes,
+    /// zero offset is written as `Z`. Returns error for offsets out of ±23:59 range
+    /// ```
+    /// use stm32f3_rtc::datetime::{Date, DateTime, Time};
+    /// let date_time = DateTime::from(Date::from(1, 6, 2024), Time::from(7, 30, 0), 0);
+    /// let mut text = String::new();
+    /// date_time.write_rfc3339(&mut text, 0, -330).unwrap();
+    /// assert_eq!(text, "2024-06-01T07:30:00-05:30");
+    /// ```
+    pub fn write_rfc3339<W: fmt::Write>(
+        &self,
+        writer: &mut W,
+        fraction_digits: u8,
+        offset: i16,
+    ) -> fmt::Result {
+        if offset.abs() >= 24 * 60 {
+            return Err(fmt::Error);
+        }
+        self.write_iso8601(writer, fraction_digits)?;
+        match offset {
+            0 => writer.write_str("Z"),
+            _ => write!(
+                writer,
+                "{}{:02}:{:02}",
+                if offset < 0 { '-' } else { '+' },
+                offset.abs() / 60,
+                offset.abs() % 60
+            ),
+        }
+    }
+}
+
+/// [fmt::Write] implementation over fixed byte buffer. Text that does not fit into
+/// remaining space is rejected as a whole, so buffer always keeps valid string.
+pub struct SliceWriter<'a> {
+    buffer: &'a mut [u8],
+    length: usize,
+}
+
+impl<'a> SliceWriter<'a> {
+    /// Create a new writer, that starts at the beginning of the buffer
+    pub fn new(buffer: &'a mut [u8]) -> Self {
+        SliceWriter { buffer, length: 0 }
+    }
+
+    /// Returns number of written bytes
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    /// Returns true if nothing was written yet
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// Returns written bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buffer[..self.length]
+    }
+
+    /// Returns written text
+    pub fn as_str(&self) -> &str {
+        // Only whole str slices are copied into the buffer
+        unsafe { str::from_utf8_unchecked(self.as_bytes()) }
+    }
+}
+
+impl<'a> fmt::Write for SliceWriter<'a> {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        let end = self.length + text.len();
+        if end > self.buffer.len() {
+            return Err(fmt::Error);
+        }
+        self.buffer[self.length..end].copy_from_slice(text.as_bytes());
+        self.length = end;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_with_offset, ParseError, SliceWriter, MAX_LENGTH};
+    use core::fmt::Write;
+    use datetime::{Date, DateTime, Time};
+
+    #[test]
+    fn invalid_dates_are_rejected() {
+        #[rustfmt::skip]
+        let cases = [
+            ("2024-00-01", ParseError::OutOfRange),
+            ("2024-13-01", ParseError::OutOfRange),
+            ("2024-01-00", ParseError::OutOfRange),
+            ("2024-04-31", ParseError::OutOfRange),
+            ("2023-02-29", ParseError::OutOfRange),
+            ("2100-02-29", ParseError::OutOfRange),
+            ("2024-06", ParseError::TooShort),
+            ("", ParseError::TooShort),
+            ("24-06-01", ParseError::InvalidCharacter(2)),
+            ("2024/06/01", ParseError::InvalidCharacter(4)),
+            ("2024-06-01Z", ParseError::TrailingCharacters),
+        ];
+        for &(text, error) in cases.iter() {
+            assert_eq!(Date::parse_iso8601(text), Err(error), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn invalid_times_are_rejected() {
+        #[rustfmt::skip]
+        let cases = [
+            ("24:00:00", ParseError::OutOfRange),
+            ("23:60:00", ParseError::OutOfRange),
+            ("23:59:60", ParseError::OutOfRange),
+            ("12:30", ParseError::TooShort),
+            ("12-30-00", ParseError::InvalidCharacter(2)),
+            ("12:30:00 ", ParseError::TrailingCharacters),
+        ];
+        for &(text, error) in cases.iter() {
+            assert_eq!(Time::parse_iso8601(text), Err(error), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn invalid_date_times_are_rejected() {
+        #[rustfmt::skip]
+        let cases = [
+            ("2024-06-01X12:30:00", ParseError::InvalidCharacter(10)),
+            ("2024-06-01T24:00:00Z", ParseError::OutOfRange),
+            // Truncated or too long fraction of second
+            ("2024-06-01T12:30:00.", ParseError::TooShort),
+            ("2024-06-01T12:30:00.Z", ParseError::InvalidCharacter(20)),
+            ("2024-06-01T12:30:00.1234567890", ParseError::InvalidCharacter(29)),
+            // Offsets out of ±23:59 range or incomplete
+            ("2024-06-01T12:30:00+24:00", ParseError::OutOfRange),
+            ("2024-06-01T12:30:00+25:00", ParseError::OutOfRange),
+            ("2024-06-01T12:30:00-01:60", ParseError::OutOfRange),
+            ("2024-06-01T12:30:00+01", ParseError::TooShort),
+            ("2024-06-01T12:30:00+0100", ParseError::InvalidCharacter(22)),
+            ("2024-06-01T12:30:00~01:00", ParseError::InvalidCharacter(19)),
+            ("2024-06-01T12:30:00ZZ", ParseError::TrailingCharacters),
+        ];
+        for &(text, error) in cases.iter() {
+            assert_eq!(parse_with_offset(text), Err(error), "{:?}", text);
+        }
+        assert_eq!(
+            DateTime::parse_iso8601("2024-06-01T12:30:00-00:01"),
+            Err(ParseError::UnexpectedOffset)
+        );
+    }
+
+    #[test]
+    fn fraction_keeps_given_digits() {
+        let date_time = DateTime::parse_iso8601("2024-06-01T12:30:00.000000001Z").unwrap();
+        assert_eq!(date_time.nanosecond, 1);
+        let date_time = DateTime::parse_iso8601("2024-06-01T12:30:00,12").unwrap();
+        assert_eq!(date_time.nanosecond, 120_000_000);
+    }
+
+    #[test]
+    fn slice_writer_rejects_text_that_does_not_fit() {
+        let date_time = DateTime::from(Date::from(1, 6, 2024), Time::from(7, 30, 0), 1);
+        let mut buffer = [0u8; MAX_LENGTH];
+        let mut writer = SliceWriter::new(&mut buffer);
+        date_time
+            .write_rfc3339(&mut writer, 9, -(23 * 60 + 59))
+            .unwrap();
+        assert_eq!(writer.as_str(), "2024-06-01T07:30:00.000000001-23:59");
+
+        let mut buffer = [0u8; 12];
+        let mut writer = SliceWriter::new(&mut buffer);
+        assert!(date_time.write_rfc3339(&mut writer, 0, 0).is_err());
+        // Text that does not fit is not written, so buffer keeps valid beginning
+        assert_eq!(writer.as_str(), "2024-06-01T0");
+        assert!(writer.write_str("7").is_err());
+        assert_eq!(writer.len(), 12);
+
+        let mut writer = SliceWriter::new(&mut buffer);
+        assert!(date_time.write_rfc3339(&mut writer, 0, 24 * 60).is_err());
+        assert!(writer.is_empty());
+    }
+}
@@ -3,8 +3,59 @@ extern crate stm32f3xx_hal;
 extern crate cortex_m_semihosting;
 extern crate cortex_m_rt;
 extern crate cortex_m;
+#[cfg(feature = "embassy")]
+extern crate critical_section;
+#[cfg(feature = "embassy")]
+extern crate embassy_time_driver;
+#[cfg(feature = "embassy")]
+extern crate embassy_time_queue_utils;
+#[cfg(feature = "rtic")]
+extern crate fugit;
+#[cfg(feature = "rtic")]
+extern crate rtic_monotonic;
+#[cfg(feature = "rtcc")]
+extern crate rtcc;
+#[cfg(feature = "embedded-sdmmc")]
+extern crate embedded_sdmmc;
+#[cfg(feature = "chrono")]
+extern crate chrono;
+#[cfg(feature = "time")]
+extern crate time;
+#[cfg(feature = "defmt")]
+extern crate defmt;
+#[cfg(feature = "serde")]
+extern crate serde;
+
+#[cfg(all(feature = "embassy", feature = "rtic"))]
+compile_error!("features \"embassy\" and \"rtic\" are both using Alarm A, please pick one of them");
 
 pub mod datetime;
+pub mod iso8601;
+pub mod strftime;
 pub mod rtc;
+pub mod century;
 pub mod wakeup;
 pub mod rtc_interrupt;
+pub mod dst;
+pub mod tz;
+pub mod offset;
+pub mod parts;
+pub mod clock;
+#[cfg(feature = "embassy")]
+pub mod time_driver;
+#[cfg(feature = "rtic")]
+pub mod monotonic;
+#[cfg(any(feature = "embassy", feature = "rtic"))]
+mod tick_counter;
+#[cfg(feature = "rtcc")]
+pub mod rtcc_access;
+#[cfg(feature = "embedded-sdmmc")]
+pub mod time_source;
+#[cfg(feature = "chrono")]
+pub mod chrono_access;
+#[cfg(feature = "time")]
+pub mod time_rs_access;
+#[cfg(feature = "defmt")]
+mod defmt_format;
+#[cfg(feature = "serde")]
+mod serde_format;
@@ -0,0 +1,87 @@
+use fugit;
+use rtc::Rtc;
+use rtic_monotonic::Monotonic;
+use stm32f3xx_hal::pac::rtc::RegisterBlock;
+use stm32f3xx_hal::pac::{EXTI, RTC};
+use tick_counter;
+
+/// RTIC monotonic that runs from the RTC, so software tasks can be scheduled days ahead
+/// and `spawn_after`/`spawn_at` keep working through **STOP** mode.
+///
+/// Instant is built from calendar (days since 1 January 2000, hours, minutes, seconds) and
+/// sub second register (SSR), compare is done with **Alarm A** with sub second comparison.
+/// Monotonic has to be bound to **RTCALARM** interrupt.
+///
+/// ## Tick rate
+/// One tick is one step of synchronous prescaler, so **TIMER_HZ** has to be equal to
+/// **PREDIV_S + 1**, e.g. **256** for LSE with default prescalers.
+///
+/// **Note:** Changing time or date with **TimeAccess** or **DateAccess** moves the monotonic,
+/// so please set calendar before monotonic is created.
+///
+/// ## Usage:
+/// ```
+/// use stm32f3_rtc::monotonic::RtcMonotonic;
+/// use stm32f3_rtc::rtc::{ClockSource, Rtc};
+/// ...
+/// #[monotonic(binds = RTCALARM, default = true)]
+/// type Mono = RtcMonotonic<256>;
+///
+/// #[init]
+/// fn init(mut cx: init::Context) -> (Shared, Local, init::Monotonics) {
+///     let mut rtc = Rtc::new(cx.device.RTC);
+///     rtc.set_clock_source(ClockSource::LSE(true));
+///     rtc.start_clock(&mut cx.device.PWR, &mut cx.device.RCC);
+///     let mono = RtcMonotonic::new(&mut rtc, &mut cx.device.EXTI);
+///     ...
+///     (Shared {}, Local {}, init::Monotonics(mono))
+/// }
+/// ```
+pub struct RtcMonotonic<const TIMER_HZ: u32> {
+    _private: (),
+}
+
+impl<const TIMER_HZ: u32> RtcMonotonic<TIMER_HZ> {
+    /// Returns new RtcMonotonic instance. It has to be created after RTC clock is started.
+    ///
+    /// It disables Alarm A for any other usage and enables **RTCALARM** interrupt
+    /// through EXTI line 17.
+    ///
+    /// **Note:** Panics if TIMER_HZ is not equal to PREDIV_S + 1
+    pub fn new(rtc: &mut Rtc, exti: &mut EXTI) -> Self {
+        assert!(
+            u64::from(TIMER_HZ) == tick_counter::tick_rate(&rtc.rtc),
+            "monotonic TIMER_HZ has to be equal to PREDIV_S + 1"
+        );
+        tick_counter::enable_alarm_interrupt(&rtc.rtc, exti);
+        Self { _private: () }
+    }
+
+    fn registers(&self) -> &'static RegisterBlock {
+        unsafe { &*RTC::PTR }
+    }
+}
+
+impl<const TIMER_HZ: u32> Monotonic for RtcMonotonic<TIMER_HZ> {
+    type Instant = fugit::Instant<u64, 1, TIMER_HZ>;
+    type Duration = fugit::Duration<u64, 1, TIMER_HZ>;
+
+    fn now(&mut self) -> Self::Instant {
+        Self::Instant::from_ticks(tick_counter::ticks(self.registers()))
+    }
+
+    fn set_compare(&mut self, instant: Self::Instant) {
+        tick_counter::set_alarm(self.registers(), instant.ticks());
+    }
+
+    fn clear_compare_flag(&mut self) {
+        tick_counter::acknowledge_alarm(self.registers());
+    }
+
+    fn zero() -> Self::Instant {
+        Self::Instant::from_ticks(0)
+    }
+
+    /// Calendar is not reset, instant zero is always 1 January 2000 00:00:00
+    unsafe fn reset(&mut self) {}
+}
@@ -0,0 +1,262 @@
+//! Fixed UTC offset (±hh:mm) and offset aware date and time, built on
+//! [datetime](crate::datetime) types.
+//!
+//! It is a simpler complement to [tz](crate::tz) zones, for devices that keep RTC in UTC
+//! and report time also at one configured installation offset.
+//!
+//! ## Usage:
+//! ```
+//! use stm32f3_rtc::datetime::{Date, DateTime, Time};
+//! use stm32f3_rtc::offset::{OffsetDateTime, UtcOffset};
+//!
+//! let utc = DateTime::from(Date::from(1, 6, 2024), Time::from(23, 30, 0), 0);
+//! let offset = UtcOffset::from_hm(5, 30).unwrap();
+//! let local = OffsetDateTime::from_utc(utc, offset);
+//! assert_eq!(format!("{}", local), "2024-06-02T05:00:00+05:30");
+//!
+//! // The same moment is equal, no matter which offset it uses
+//! assert_eq!(local, OffsetDateTime::from(utc, UtcOffset::UTC));
+//! assert_eq!(local.to_utc(), utc);
+//! ```
+use core::cmp::Ordering;
+use core::fmt;
+use datetime::DateTime;
+use iso8601::{parse_with_offset, ParseError};
+use rtc::Rtc;
+
+/// Offset from UTC with minute resolution, in ±23:59 range
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct UtcOffset {
+    minutes: i16,
+}
+
+impl UtcOffset {
+    /// Zero offset
+    pub const UTC: UtcOffset = UtcOffset { minutes: 0 };
+
+    /// Create offset from minutes east of UTC, returns None when it is out of ±23:59 range
+    pub fn from_minutes(minutes: i16) -> Option<UtcOffset> {
+        match minutes.abs() < 24 * 60 {
+            true => Some(UtcOffset { minutes }),
+            false => None,
+        }
+    }
+
+    /// Create offset from hours and minutes east of UTC, both have to use the same sign
+    /// (e.g. -3:30 is `from_hm(-3, -30)`)
+    /// ```
+    /// use stm32f3_rtc::offset::UtcOffset;
+    /// assert_eq!(UtcOffset::from_hm(-3, -30).unwrap().minutes(), -210);
+    /// assert!(UtcOffset::from_hm(-3, 30).is_none());
+    /// assert!(UtcOffset::from_hm(24, 0).is_none());
+    /// ```
+    pub fn from_hm(hours: i8, minutes: i8) -> Option<UtcOffset> {
+        if minutes.abs() > 59 || (hours < 0 && minutes > 0) || (hours > 0 && minutes < 0) {
+            return None;
+        }
+        Self::from_minutes(i16::from(hours) * 60 + i16::from(minutes))
+    }
+
+    /// Returns offset in minutes east of UTC
+    pub fn minutes(&self) -> i16 {
+        self.minutes
+    }
+
+    /// Returns offset in seconds east of UTC
+    pub fn seconds(&self) -> i32 {
+        i32::from(self.minutes) * 60
+    }
+}
+
+/// Formats offset as ±hh:mm
+/// ```
+/// use stm32f3_rtc::offset::UtcOffset;
+/// assert_eq!(format!("{}", UtcOffset::from_minutes(-330).unwrap()), "-05:30");
+/// assert_eq!(format!("{}", UtcOffset::UTC), "+00:00");
+/// ```
+impl fmt::Display for UtcOffset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = match self.minutes < 0 {
+            true => '-',
+            false => '+',
+        };
+        let minutes = self.minutes.abs();
+        write!(f, "{}{:02}:{:02}", sign, minutes / 60, minutes % 60)
+    }
+}
+
+/// Local date and time together with its UTC offset. Values are compared by the moment they
+/// describe, so `12:00+01:00` is equal to `11:00Z`.
+#[derive(Clone, Copy, Debug)]
+pub struct OffsetDateTime {
+    /// Local date and time at the offset
+    pub date_time: DateTime,
+    pub offset: UtcOffset,
+}
+
+impl OffsetDateTime {
+    /// Create a new OffsetDateTime struct from following arguments,
+    /// (local date_time, offset)
+    pub fn from(date_time: DateTime, offset: UtcOffset) -> OffsetDateTime {
+        OffsetDateTime { date_time, offset }
+    }
+
+    /// Create a new OffsetDateTime from UTC date and time, converted to given offset
+    pub fn from_utc(utc: DateTime, offset: UtcOffset) -> OffsetDateTime {
+        OffsetDateTime {
+            date_time: utc.add_seconds(offset.seconds()),
+            offset,
+        }
+    }
+
+    /// Returns date and time normalized to UTC
+    pub fn to_utc(&self) -> DateTime {
+        self.date_time.add_seconds(-self.offset.seconds())
+    }
+
+    /// Returns the same moment at another offset
+    pub fn to_offset(&self, offset: UtcOffset) -> OffsetDateTime {
+        Self::from_utc(self.to_utc(), offset)
+    }
+
+    /// Parses RFC 3339 date and time, offset is required
+    /// ```
+    /// use stm32f3_rtc::offset::OffsetDateTime;
+    /// use stm32f3_rtc::iso8601::ParseError;
+    /// let value = OffsetDateTime::parse_rfc3339("2024-06-01T07:30:00-05:00").unwrap();
+    /// assert_eq!(value.to_utc().time.hour, 12);
+    /// assert_eq!(
+    ///     OffsetDateTime::parse_rfc3339("2024-06-01T07:30:00"),
+    ///     Err(ParseError::MissingOffset)
+    /// );
+    /// ```
+    pub fn parse_rfc3339(text: &str) -> Result<OffsetDateTime, ParseError> {
+        match parse_with_offset(text)? {
+            (date_time, Some(minutes)) => Ok(OffsetDateTime {
+                date_time,
+                // Parser accepts only offsets in ±23:59 range
+                offset: UtcOffset { minutes },
+            }),
+            (_, None) => Err(ParseError::MissingOffset),
+        }
+    }
+
+    /// Returns UTC moment as tuple that can be compared
+    fn key(&self) -> (u32, u8, u8, u8, u8, u8, u32) {
+        let utc = self.to_utc();
+        (
+            utc.date.year,
+            utc.date.month,
+            utc.date.day,
+            utc.time.hour,
+            utc.time.minute,
+            utc.time.second,
+            utc.nanosecond,
+        )
+    }
+}
+
+impl PartialEq for OffsetDateTime {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for OffsetDateTime {}
+
+impl PartialOrd for OffsetDateTime {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OffsetDateTime {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// Formats date and time as RFC 3339 (YYYY-MM-DDThh:mm:ss±hh:mm), zero offset is written
+/// as `Z` and milliseconds are added when fraction of second is present
+/// ```
+/// use stm32f3_rtc::datetime::{Date, DateTime, Time};
+/// use stm32f3_rtc::offset::{OffsetDateTime, UtcOffset};
+/// let utc = DateTime::from(Date::from(1, 6, 2024), Time::from(12, 30, 0), 0);
+/// assert_eq!(
+///     format!("{}", OffsetDateTime::from(utc, UtcOffset::UTC)),
+///     "2024-06-01T12:30:00Z"
+/// );
+/// ```
+impl fmt::Display for OffsetDateTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.offset.minutes {
+            0 => write!(f, "{}Z", self.date_time),
+            _ => write!(f, "{}{}", self.date_time, self.offset),
+        }
+    }
+}
+
+impl Rtc {
+    /// Returns current date and time at given offset, RTC has to keep UTC
+    /// ```
+    /// let local = rtc.offset_date_time(UtcOffset::from_hm(2, 0).unwrap());
+    /// hprintln!("UTC: {} local: {}", local.to_utc(), local);
+    /// ```
+    pub fn offset_date_time(&self, offset: UtcOffset) -> OffsetDateTime {
+        OffsetDateTime::from_utc(self.date_time(), offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OffsetDateTime, UtcOffset};
+    use datetime::{Date, DateTime, Time};
+    use iso8601::ParseError;
+
+    #[test]
+    fn offsets_out_of_range_are_rejected() {
+        assert_eq!(UtcOffset::from_minutes(24 * 60), None);
+        assert_eq!(UtcOffset::from_minutes(-24 * 60), None);
+        assert_eq!(
+            UtcOffset::from_minutes(-(23 * 60 + 59)).unwrap().seconds(),
+            -86_340
+        );
+        assert_eq!(UtcOffset::from_hm(25, 0), None);
+        assert_eq!(UtcOffset::from_hm(1, 60), None);
+        assert_eq!(UtcOffset::from_hm(1, -30), None);
+        assert_eq!(UtcOffset::from_hm(0, -30).unwrap().minutes(), -30);
+    }
+
+    #[test]
+    fn rfc3339_requires_valid_offset() {
+        #[rustfmt::skip]
+        let cases = [
+            ("2024-06-01T07:30:00", ParseError::MissingOffset),
+            ("2024-06-01T07:30:00.5", ParseError::MissingOffset),
+            ("2024-06-01T07:30:00+25:00", ParseError::OutOfRange),
+            ("2024-06-01T07:30:00+05", ParseError::TooShort),
+            ("2024-06-31T07:30:00Z", ParseError::OutOfRange),
+            ("2024-06-01T07:30:00+05:00 ", ParseError::TrailingCharacters),
+        ];
+        for &(text, error) in cases.iter() {
+            assert_eq!(
+                OffsetDateTime::parse_rfc3339(text),
+                Err(error),
+                "{:?}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn moments_are_compared_in_utc() {
+        let utc = DateTime::from(Date::from(31, 12, 2024), Time::from(23, 30, 0), 0);
+        let east = OffsetDateTime::from_utc(utc, UtcOffset::from_hm(1, 0).unwrap());
+        let west = OffsetDateTime::from_utc(utc, UtcOffset::from_hm(-5, 0).unwrap());
+        assert_eq!(east.date_time.date, Date::from(1, 1, 2025));
+        assert_eq!(east, west);
+        assert_eq!(east.to_offset(UtcOffset::UTC).date_time, utc);
+        let later = OffsetDateTime::from_utc(utc.add_seconds(1), UtcOffset::UTC);
+        assert!(later > west);
+    }
+}
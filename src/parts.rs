@@ -0,0 +1,484 @@
+//! Split RTC into separately owned parts, so each of them can be moved into a different task,
+//! interrupt or `Mutex`.
+//!
+//! Parts access only their own registers. Write protection and init mode are handled inside
+//! of critical sections, so parts do not break writes of each other.
+//!
+//! ## Usage:
+//! ```
+//! use stm32f3_rtc::datetime::{Time, TimeAccess};
+//! use stm32f3_rtc::rtc::Rtc;
+//! use stm32f3xx_hal::pac;
+//!
+//! let mut peripheral = pac::Peripherals::take().unwrap();
+//! let mut rtc = Rtc::new(peripheral.RTC);
+//! rtc.start_clock(&mut peripheral.PWR, &mut peripheral.RCC);
+//! let mut parts = rtc.split();
+//! parts.calendar.set_time(Time::from(12, 30, 0));
+//! parts.wakeup.manager().set_counter(10).enable();
+//! parts.backup.write(1, 0xCAFE).unwrap();
+//! ```
+use century;
+use datetime::{
+    AmPm, Bcd, BcdDate, BcdTime, ConversionError, Date, DateAccess, DateTime, Time, Time12,
+    TimeAccess,
+};
+use rtc::{
+    init_mode, read_calendar, read_date_time, unprotected, write_date, write_date_time, write_time,
+    Rtc,
+};
+use stm32f3xx_hal::pac::rtc::RegisterBlock;
+use stm32f3xx_hal::pac::RTC;
+use wakeup::WakeupManager;
+
+/// Number of RTC backup registers
+const BACKUP_REGISTERS: usize = 16;
+
+/// Returns RTC registers, parts are created only from owned RTC peripheral
+fn registers() -> &'static RegisterBlock {
+    unsafe { &*RTC::PTR }
+}
+
+/// All parts of split RTC
+pub struct Parts {
+    pub calendar: Calendar,
+    pub wakeup: Wakeup,
+    pub alarms: Alarms,
+    pub timestamp: Timestamp,
+    pub tamper: Tamper,
+    pub backup: Backup,
+    pub calibration: Calibration,
+}
+
+impl Rtc {
+    /// Splits RTC into separately owned parts, clock has to be already started
+    pub fn split(self) -> Parts {
+        Parts {
+            calendar: Calendar { _private: () },
+            wakeup: Wakeup { _private: () },
+            alarms: Alarms { _private: () },
+            timestamp: Timestamp { _private: () },
+            tamper: Tamper { _private: () },
+            backup: Backup { _private: () },
+            calibration: Calibration { _private: () },
+        }
+    }
+}
+
+/// Edge of the input signal, that triggers an event
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Edge {
+    Rising,
+    Falling,
+}
+
+/// Date and time access
+pub struct Calendar {
+    _private: (),
+}
+
+impl Calendar {
+    /// Returns date and time read at once, including fraction of second
+    pub fn date_time(&self) -> DateTime {
+        read_date_time(registers())
+    }
+
+    /// Saves century rollover and skipped leap day, see **Rtc::update_century()**
+    pub fn update_century(&mut self) {
+        century::update(registers())
+    }
+
+    /// Set date and time at once, see **Rtc::set_date_time()**
+    pub fn set_date_time(&mut self, date_time: DateTime) -> Result<(), ConversionError> {
+        write_date_time(registers(), &date_time)
+    }
+}
+
+impl TimeAccess for Calendar {
+    /// Returns current time as Time struct, in 24 hour format
+    fn time(&self) -> Time {
+        read_calendar(registers()).1
+    }
+
+    /// Set time by Time struct
+    fn set_time(&mut self, time: Time) {
+        init_mode(registers(), |rtc| write_time(rtc, &time))
+    }
+}
+
+impl DateAccess for Calendar {
+    /// Returns current date as Date struct
+    fn date(&self) -> Date {
+        read_calendar(registers()).0
+    }
+
+    /// Set date with Date struct, see **DateAccess::set_date()** of Rtc
+    fn set_date(&mut self, date: Date) {
+        if let Ok(date) = century::store(registers(), &date) {
+            init_mode(registers(), |rtc| write_date(rtc, &BcdDate::from(date)))
+        }
+    }
+}
+
+/// Wake up timer
+pub struct Wakeup {
+    _private: (),
+}
+
+impl Wakeup {
+    /// Returns WakeupManager, that configures wake up timer
+    pub fn manager(&mut self) -> WakeupManager<'_> {
+        WakeupManager::from_registers(registers())
+    }
+}
+
+/// Alarm A or B
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Alarm {
+    A,
+    B,
+}
+
+impl Alarm {
+    fn index(self) -> usize {
+        match self {
+            Alarm::A => 0,
+            Alarm::B => 1,
+        }
+    }
+}
+
+/// Alarms A and B
+///
+/// **Note:** Alarm A is used by embassy time driver and RTIC monotonic
+pub struct Alarms {
+    _private: (),
+}
+
+impl Alarms {
+    /// Set alarm to given time, every day or only on given day of the month
+    /// ```
+    /// parts.alarms.set(Alarm::B, Some(15), &Time::from(6, 0, 0));
+    /// ```
+    pub fn set(&mut self, alarm: Alarm, day: Option<u8>, time: &Time) {
+        let rtc = registers();
+        let (hour, pm) = match rtc.cr.read().fmt().bit_is_set() {
+            true => {
+                let time: Time12 = (*time).into();
+                (time.hour, time.am_pm == AmPm::PM)
+            }
+            false => (time.hour, false),
+        };
+        let bcd_time = BcdTime::from(Time::from(hour, time.minute, time.second));
+        let bcd_day = Bcd::set(day.unwrap_or(1));
+        unprotected(rtc, |rtc| {
+            self.enable_alarm(rtc, alarm, false);
+            rtc.alrmr[alarm.index()].write(|w| {
+                w.msk4().bit(day.is_none());
+                w.wdsel().clear_bit();
+                w.dt().bits(bcd_day.tens);
+                w.du().bits(bcd_day.units);
+                w.msk3().clear_bit();
+                w.pm().bit(pm);
+                w.ht().bits(bcd_time.hour.tens);
+                w.hu().bits(bcd_time.hour.units);
+                w.msk2().clear_bit();
+                w.mnt().bits(bcd_time.minutes.tens);
+                w.mnu().bits(bcd_time.minutes.units);
+                w.msk1().clear_bit();
+                w.st().bits(bcd_time.seconds.tens);
+                w.su().bits(bcd_time.seconds.units)
+            });
+            // Sub seconds are not compared
+            rtc.alrmssr[alarm.index()].write(|w| w.maskss().bits(0));
+            self.enable_alarm(rtc, alarm, true);
+        })
+    }
+
+    /// Disables alarm
+    pub fn disable(&mut self, alarm: Alarm) {
+        unprotected(registers(), |rtc| self.enable_alarm(rtc, alarm, false))
+    }
+
+    /// Enable/Disable alarm interrupt, EXTI line 17 has to be configured by application
+    pub fn set_interrupt(&mut self, alarm: Alarm, enable: bool) {
+        unprotected(registers(), |rtc| {
+            rtc.cr.modify(|_, w| match alarm {
+                Alarm::A => w.alraie().bit(enable),
+                Alarm::B => w.alrbie().bit(enable),
+            })
+        })
+    }
+
+    /// Returns true when alarm time was reached
+    pub fn is_triggered(&self, alarm: Alarm) -> bool {
+        let isr = registers().isr.read();
+        match alarm {
+            Alarm::A => isr.alraf().bit_is_set(),
+            Alarm::B => isr.alrbf().bit_is_set(),
+        }
+    }
+
+    /// Clears alarm flag
+    pub fn clear(&mut self, alarm: Alarm) {
+        unprotected(registers(), |rtc| {
+            rtc.isr.modify(|_, w| match alarm {
+                Alarm::A => w.alraf().clear_bit(),
+                Alarm::B => w.alrbf().clear_bit(),
+            })
+        })
+    }
+
+    /// Enables/Disables alarm, write protection has to be disabled. Alarm register can be
+    /// written after alarm is disabled and write flag is set.
+    fn enable_alarm(&self, rtc: &RegisterBlock, alarm: Alarm, enable: bool) {
+        match alarm {
+            Alarm::A => {
+                rtc.cr.modify(|_, w| w.alrae().bit(enable));
+                rtc.isr.modify(|_, w| w.alraf().clear_bit());
+                while !enable && rtc.isr.read().alrawf().bit_is_clear() {}
+            }
+            Alarm::B => {
+                rtc.cr.modify(|_, w| w.alrbe().bit(enable));
+                rtc.isr.modify(|_, w| w.alrbf().clear_bit());
+                while !enable && rtc.isr.read().alrbwf().bit_is_clear() {}
+            }
+        }
+    }
+}
+
+/// Moment saved by time stamp event, register does not keep year
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimestampEvent {
+    pub day: u8,
+    pub month: u8,
+    /// Time in 24 hour format
+    pub time: Time,
+    /// Another time stamp event happened before this one was read, its time was not saved
+    pub overflow: bool,
+}
+
+/// Time stamp of RTC_TS input events
+pub struct Timestamp {
+    _private: (),
+}
+
+impl Timestamp {
+    /// Enables saving of calendar on given edge of RTC_TS input
+    pub fn enable(&mut self, edge: Edge) {
+        unprotected(registers(), |rtc| {
+            // Edge can be changed only when time stamp is disabled
+            rtc.cr.modify(|_, w| w.tse().clear_bit());
+            rtc.cr.modify(|_, w| w.tsedge().bit(edge == Edge::Falling));
+            rtc.cr.modify(|_, w| w.tse().set_bit())
+        })
+    }
+
+    /// Disables time stamp
+    pub fn disable(&mut self) {
+        unprotected(registers(), |rtc| rtc.cr.modify(|_, w| w.tse().clear_bit()))
+    }
+
+    /// Returns saved time stamp and clears it, None when no event happened
+    /// ```
+    /// if let Some(event) = parts.timestamp.read() {
+    ///     hprintln!("{}.{}. {}", event.day, event.month, event.time);
+    /// }
+    /// ```
+    pub fn read(&mut self) -> Option<TimestampEvent> {
+        let rtc = registers();
+        if rtc.isr.read().tsf().bit_is_clear() {
+            return None;
+        }
+        let tstr = rtc.tstr.read();
+        let tsdr = rtc.tsdr.read();
+        let hour = Bcd {
+            tens: tstr.ht().bits(),
+            units: tstr.hu().bits(),
+        }
+        .get();
+        let hour = match (rtc.cr.read().fmt().bit_is_set(), tstr.pm().bit_is_set()) {
+            (false, _) => hour,
+            (true, pm) => hour % 12 + if pm { 12 } else { 0 },
+        };
+        let minute = Bcd {
+            tens: tstr.mnt().bits(),
+            units: tstr.mnu().bits(),
+        };
+        let second = Bcd {
+            tens: tstr.st().bits(),
+            units: tstr.su().bits(),
+        };
+        // Time stamp registers are valid until TSF is cleared. Event that comes before it is
+        // cleared sets only TSOVF, so TSOVF is checked and cleared after TSF.
+        let overflow = unprotected(rtc, |rtc| {
+            rtc.isr.modify(|_, w| w.tsf().clear_bit());
+            let overflow = rtc.isr.read().tsovf().bit_is_set();
+            if overflow {
+                rtc.isr.modify(|_, w| w.tsovf().clear_bit());
+            }
+            overflow
+        });
+        Some(TimestampEvent {
+            day: Bcd {
+                tens: tsdr.dt().bits(),
+                units: tsdr.du().bits(),
+            }
+            .get(),
+            month: Bcd {
+                tens: u8::from(tsdr.mt().bit()),
+                units: tsdr.mu().bits(),
+            }
+            .get(),
+            time: Time::from(hour, minute.get(), second.get()),
+            overflow,
+        })
+    }
+}
+
+/// Tamper input
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TamperInput {
+    Tamp1,
+    Tamp2,
+    Tamp3,
+}
+
+/// Tamper detection on RTC_TAMPx inputs
+///
+/// **Note:** Tamper event resets all backup registers
+pub struct Tamper {
+    _private: (),
+}
+
+impl Tamper {
+    /// Enables edge detection on tamper input
+    pub fn enable(&mut self, input: TamperInput, edge: Edge) {
+        let falling = edge == Edge::Falling;
+        unprotected(registers(), |rtc| {
+            rtc.tafcr.modify(|_, w| match input {
+                TamperInput::Tamp1 => w.tamp1trg().bit(falling).tamp1e().set_bit(),
+                TamperInput::Tamp2 => w.tamp2trg().bit(falling).tamp2e().set_bit(),
+                TamperInput::Tamp3 => w.tamp3trg().bit(falling).tamp3e().set_bit(),
+            })
+        })
+    }
+
+    /// Disables tamper input
+    pub fn disable(&mut self, input: TamperInput) {
+        unprotected(registers(), |rtc| {
+            rtc.tafcr.modify(|_, w| match input {
+                TamperInput::Tamp1 => w.tamp1e().clear_bit(),
+                TamperInput::Tamp2 => w.tamp2e().clear_bit(),
+                TamperInput::Tamp3 => w.tamp3e().clear_bit(),
+            })
+        })
+    }
+
+    /// Returns true when tamper event was detected
+    pub fn is_triggered(&self, input: TamperInput) -> bool {
+        let isr = registers().isr.read();
+        match input {
+            TamperInput::Tamp1 => isr.tamp1f().bit_is_set(),
+            TamperInput::Tamp2 => isr.tamp2f().bit_is_set(),
+            TamperInput::Tamp3 => isr.tamp3f().bit_is_set(),
+        }
+    }
+
+    /// Clears tamper flag
+    pub fn clear(&mut self, input: TamperInput) {
+        unprotected(registers(), |rtc| {
+            rtc.isr.modify(|_, w| match input {
+                TamperInput::Tamp1 => w.tamp1f().clear_bit(),
+                TamperInput::Tamp2 => w.tamp2f().clear_bit(),
+                TamperInput::Tamp3 => w.tamp3f().clear_bit(),
+            })
+        })
+    }
+}
+
+/// Errors of backup register access
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BackupError {
+    /// RTC has only 16 backup registers (0 - 15)
+    OutOfRange,
+    /// Register keeps century tracked by **Rtc::track_century()**
+    Reserved,
+}
+
+/// Backup registers (0 - 15), they keep value while backup domain is powered
+///
+/// **Note:** Register used by **Rtc::track_century()** is reserved, it cannot be read or
+/// written through this part
+pub struct Backup {
+    _private: (),
+}
+
+impl Backup {
+    /// Returns value of backup register
+    pub fn read(&self, register: usize) -> Result<u32, BackupError> {
+        check_register(register)?;
+        Ok(registers().bkpr[register].read().bits())
+    }
+
+    /// Writes value into backup register
+    pub fn write(&mut self, register: usize, value: u32) -> Result<(), BackupError> {
+        check_register(register)?;
+        registers().bkpr[register].write(|w| w.bits(value));
+        Ok(())
+    }
+}
+
+/// Checks, that backup register exists and it does not keep century
+fn check_register(register: usize) -> Result<(), BackupError> {
+    if register >= BACKUP_REGISTERS {
+        return Err(BackupError::OutOfRange);
+    }
+    match century::tracked_register() == Some(register) {
+        true => Err(BackupError::Reserved),
+        false => Ok(()),
+    }
+}
+
+/// Smooth digital calibration of RTC clock
+pub struct Calibration {
+    _private: (),
+}
+
+impl Calibration {
+    /// Set calibration as amount of RTCCLK pulses in 32 second cycle (2^20 pulses with
+    /// 32.768 kHz clock), that are added (512 when **add_pulses** is true) and masked
+    /// (**mask_pulses**, 0 - 511). Correction is **(512 * add_pulses - mask_pulses) / 2^20**.
+    pub fn set(&mut self, add_pulses: bool, mask_pulses: u16) {
+        unprotected(registers(), |rtc| {
+            while rtc.isr.read().recalpf().bit_is_set() {}
+            rtc.calr.write(|w| {
+                w.calp().bit(add_pulses);
+                w.calm().bits(mask_pulses.min(511))
+            })
+        })
+    }
+
+    /// Set calibration in ppm, range is from -487.1 to +488.5 ppm
+    /// ```
+    /// // Clock is 20 ppm too slow
+    /// parts.calibration.set_ppm(20.0);
+    /// ```
+    pub fn set_ppm(&mut self, ppm: f32) {
+        let pulses = ppm * 1_048_576.0 / 1_000_000.0;
+        let pulses = match pulses < 0.0 {
+            true => (pulses - 0.5) as i32,
+            false => (pulses + 0.5) as i32,
+        };
+        match pulses > 0 {
+            true => self.set(true, (512 - pulses.min(512)) as u16),
+            false => self.set(false, (-pulses).min(511) as u16),
+        }
+    }
+
+    /// Returns calibration as (add_pulses, mask_pulses)
+    pub fn get(&self) -> (bool, u16) {
+        let calr = registers().calr.read();
+        (calr.calp().bit_is_set(), calr.calm().bits())
+    }
+}
@@ -1,18 +1,66 @@
 use crate::datetime::{Bcd, BcdDate, BcdTime, DateAccess, TimeAccess};
-use datetime::{Date, Time};
+use century;
+use cortex_m::interrupt::free;
+use datetime::{AmPm, ConversionError, Date, DateTime, Time, Time12};
+use stm32f3xx_hal::pac::rtc::{dr, tr, RegisterBlock};
 use stm32f3xx_hal::pac::{PWR, RCC, RTC};
-use wakeup::WakeupManager;
+use tz::TimeZone;
+use wakeup::{self, WakeupManager};
 
-enum Init {
-    Start,
-    Stop,
-}
-
-pub(crate) enum Protection {
+enum Protection {
     Enable,
     Disable,
 }
 
+impl Protection {
+    /// Enable/Disable write protection directly on RTC registers, please use [unprotected]
+    /// so it is done in critical section
+    fn apply(self, rtc: &RegisterBlock) {
+        match self {
+            Protection::Disable => {
+                rtc.wpr.write(|w| w.key().bits(0xCA));
+                rtc.wpr.write(|w| w.key().bits(0x53))
+            }
+            Protection::Enable => rtc.wpr.write(|w| w.key().bits(0xC0)),
+        }
+    }
+}
+
+/// Runs function with write protection disabled. It is done in critical section, so
+/// protection is not enabled again by someone else in the middle of the write (e.g. by other
+/// part of split RTC used in interrupt)
+pub(crate) fn unprotected<F, R>(rtc: &RegisterBlock, function: F) -> R
+where
+    F: FnOnce(&RegisterBlock) -> R,
+{
+    free(|_| {
+        Protection::Disable.apply(rtc);
+        let result = function(rtc);
+        Protection::Enable.apply(rtc);
+        result
+    })
+}
+
+/// Runs function in init mode, when calendar and prescalers can be written
+pub(crate) fn init_mode<F>(rtc: &RegisterBlock, function: F)
+where
+    F: FnOnce(&RegisterBlock),
+{
+    unprotected(rtc, |rtc| {
+        let started = rtc.isr.read().init().bit_is_clear();
+        if started {
+            rtc.isr.modify(|_, w| w.init().set_bit());
+            while rtc.isr.read().initf().bit_is_clear() {}
+        }
+        function(rtc);
+        if started {
+            rtc.isr.modify(|_, w| w.init().clear_bit());
+            while !rtc.isr.read().initf().bit_is_clear() {}
+        }
+        wakeup::calendar_changed();
+    })
+}
+
 /// Offers clock source options LSI, LSE and HSE. Two of this source can have bypass on,
 /// by filling bool parameter
 pub enum ClockSource {
@@ -36,6 +84,17 @@ pub enum ClockSource {
     HSE(bool),
 }
 
+/// Hour format kept by RTC time registers. It does not change how time is passed to
+/// [TimeAccess], which always uses 24 hour [Time], but it decides how RTC keeps hours
+/// (e.g. for alarms or external tools reading registers).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HourFormat {
+    /// 24 hour format (0 - 23)
+    H24,
+    /// 12 hour format (1 - 12) with AM/PM bit
+    H12,
+}
+
 struct Prediv {
     a: u8,
     s: u16,
@@ -112,20 +171,24 @@ struct Prediv {
 /// ```
 pub struct Rtc {
     pub(crate) rtc: RTC,
-    unlock_key: (u8, u8),
     source: ClockSource,
     prediv: Prediv,
     default: bool,
+    hour_format: HourFormat,
+    started: bool,
+    pub(crate) time_zone: TimeZone<'static>,
 }
 
 impl Rtc {
     pub fn new(rtc: RTC) -> Self {
         Self {
             rtc,
-            unlock_key: (0xCA, 0x53),
             source: ClockSource::LSI,
             prediv: Prediv { a: 127, s: 319 },
             default: true,
+            hour_format: HourFormat::H24,
+            started: false,
+            time_zone: TimeZone::UTC,
         }
     }
 
@@ -161,14 +224,156 @@ impl Rtc {
         self
     }
 
+    /// Pick hour format used by RTC, by default it is 24 hour format.
+    ///
+    /// When the clock is already running, current time is converted into the new format.
+    ///
+    /// **Note:** Changing format of running clock resets sub second counter
+    /// ```
+    /// use stm32f3_rtc::rtc::{HourFormat, Rtc};
+    /// use stm32f3xx_hal::pac;
+    ///
+    /// let mut peripheral = pac::Peripherals::take().unwrap();
+    /// let mut rtc = Rtc::new(peripheral.RTC);
+    /// rtc.set_hour_format(HourFormat::H12);
+    /// rtc.start_clock(&mut peripheral.PWR, &mut peripheral.RCC);
+    /// ```
+    pub fn set_hour_format(&mut self, hour_format: HourFormat) -> &Self {
+        self.hour_format = hour_format;
+        if self.started {
+            let time = self.time();
+            self.modify(|rtc| {
+                rtc.cr
+                    .modify(|_, w| w.fmt().bit(hour_format == HourFormat::H12));
+                write_time(rtc, &time)
+            });
+        }
+        self
+    }
+
+    /// Returns hour format that RTC currently uses
+    pub fn hour_format(&self) -> HourFormat {
+        match self.rtc.cr.read().fmt().bit_is_set() {
+            true => HourFormat::H12,
+            false => HourFormat::H24,
+        }
+    }
+
+    /// Returns current time in 12 hour format, no matter which format RTC uses
+    pub fn time_12(&self) -> Time12 {
+        self.time().into()
+    }
+
+    /// Set time given in 12 hour format, it is converted to the format that RTC uses
+    /// ```
+    /// rtc.set_time_12(Time12::from(7, 30, 0, AmPm::PM));
+    /// ```
+    pub fn set_time_12(&mut self, time: Time12) {
+        self.set_time(time.into())
+    }
+
+    /// Moves running calendar one hour forward (summer time) without entering init mode,
+    /// and marks it in RTC backup bit, so the shift is applied only once.
+    ///
+    /// Returns false when summer time was already applied.
+    ///
+    /// **Note:** Backup bit is kept in backup domain, so it survives reset only when clock is
+    /// started by **resume_clock()** (backup domain is reset by **start_clock()**)
+    /// ```
+    /// if rtc.apply_summer_time() {
+    ///     hprintln!("Clock moved forward");
+    /// }
+    /// ```
+    pub fn apply_summer_time(&mut self) -> bool {
+        if self.is_summer_time() {
+            return false;
+        }
+        unprotected(&self.rtc, |rtc| {
+            rtc.cr.modify(|_, w| {
+                w.add1h().set_bit();
+                w.bkp().set_bit()
+            });
+            wakeup::calendar_changed()
+        });
+        true
+    }
+
+    /// Moves running calendar one hour back (winter time) without entering init mode,
+    /// and clears summer time mark in RTC backup bit.
+    ///
+    /// Returns false when summer time was not applied, or when current hour is 0, because
+    /// RTC does not subtract an hour from midnight (it would have to change the date)
+    pub fn apply_winter_time(&mut self) -> bool {
+        if !self.is_summer_time() || self.time().hour == 0 {
+            return false;
+        }
+        unprotected(&self.rtc, |rtc| {
+            rtc.cr.modify(|_, w| {
+                w.sub1h().set_bit();
+                w.bkp().clear_bit()
+            });
+            wakeup::calendar_changed()
+        });
+        true
+    }
+
+    /// Sets or clears summer time mark in RTC backup bit without moving the clock, e.g. after
+    /// **start_clock()** cleared it and local time was set
+    /// ```
+    /// rtc.set_time(Time::from(12, 0, 0));
+    /// rtc.mark_summer_time(true);
+    /// ```
+    pub fn mark_summer_time(&mut self, summer_time: bool) {
+        unprotected(&self.rtc, |rtc| {
+            rtc.cr.modify(|_, w| w.bkp().bit(summer_time))
+        })
+    }
+
+    /// Returns true when summer time is applied (RTC backup bit is set)
+    pub fn is_summer_time(&self) -> bool {
+        self.rtc.cr.read().bkp().bit_is_set()
+    }
+
     /// Starts RTC clock
     pub fn start_clock(&mut self, pwr: &mut PWR, rcc: &mut RCC) -> &mut Self {
         self.enable_clock_source(rcc)
             .enable_bdr(rcc, pwr)
             .enable_rtc(rcc);
 
-        self.rtc.cr.modify(|_, w| w.fmt().set_bit());
+        let hour_format = self.hour_format;
+        self.modify(|rtc| {
+            rtc.cr
+                .modify(|_, w| w.fmt().bit(hour_format == HourFormat::H12))
+        });
         self.set_prediv();
+        self.started = true;
+        self
+    }
+
+    /// Starts RTC clock, but keeps running calendar, prescalers, hour format and backup
+    /// registers when RTC was already initialized (e.g. before MCU reset, or it is kept by
+    /// backup battery). Uninitialized RTC is started by **start_clock()**.
+    /// ```
+    /// use stm32f3_rtc::rtc::{ClockSource, Rtc};
+    /// use stm32f3xx_hal::pac;
+    ///
+    /// let mut peripheral = pac::Peripherals::take().unwrap();
+    /// let mut rtc = Rtc::new(peripheral.RTC);
+    /// rtc.set_clock_source(ClockSource::LSE(true));
+    /// rtc.resume_clock(&mut peripheral.PWR, &mut peripheral.RCC);
+    /// ```
+    pub fn resume_clock(&mut self, pwr: &mut PWR, rcc: &mut RCC) -> &mut Self {
+        self.enable_bdr(rcc, pwr);
+        let running = rcc.bdcr.read().rtcen().bit_is_set();
+        if !running || self.rtc.isr.read().inits().bit_is_clear() {
+            return self.start_clock(pwr, rcc);
+        }
+        self.enable_clock_source(rcc);
+        self.hour_format = self.hour_format();
+        // Shadow registers are not synchronized with calendar after reset
+        unprotected(&self.rtc, |rtc| rtc.isr.modify(|_, w| w.rsf().clear_bit()));
+        while self.rtc.isr.read().rsf().bit_is_clear() {}
+        self.started = true;
         self
     }
 
@@ -191,47 +396,47 @@ impl Rtc {
         while self.time().to_seconds() < stop_sleep {}
     }
 
-    pub(crate) fn modify<F>(&mut self, mut function: F)
+    /// Returns milliseconds of current second, calculated from sub second register
+    /// and synchronous prescaler.
+    ///
+    /// **Note:** Resolution depends on prescaler, with LSE default prescalers it is ~4 ms
+    pub fn milliseconds(&self) -> u16 {
+        let prediv_s = u32::from(self.rtc.prer.read().prediv_s().bits());
+        let (_, _, ssr) = read_calendar(&self.rtc);
+        let ssr = u32::from(ssr).min(prediv_s);
+        ((prediv_s - ssr) * 1000 / (prediv_s + 1)) as u16
+    }
+
+    /// Returns current date and time read at once, together with fraction of second
+    /// calculated from sub second register
+    /// ```
+    /// let date_time = rtc.date_time();
+    /// hprintln!("{}.{}.{} {}:{}:{}", date_time.date.day, date_time.date.month,
+    ///     date_time.date.year, date_time.time.hour, date_time.time.minute, date_time.time.second);
+    /// ```
+    pub fn date_time(&self) -> DateTime {
+        read_date_time(&self.rtc)
+    }
+
+    /// Set date and time at once. Fraction of second is applied by shifting sub second
+    /// counter, so its accuracy depends on synchronous prescaler.
+    ///
+    /// Returns error and keeps calendar unchanged, when year is out of range given by
+    /// **Rtc::year_range()**
+    pub fn set_date_time(&mut self, date_time: DateTime) -> Result<(), ConversionError> {
+        write_date_time(&self.rtc, &date_time)
+    }
+
+    pub(crate) fn modify<F>(&mut self, function: F)
     where
-        F: FnMut(&mut RTC),
+        F: FnOnce(&RegisterBlock),
     {
-        self.write_protection(Protection::Disable);
-        self.initf(Init::Start);
-        function(&mut self.rtc);
-        self.initf(Init::Stop);
-        self.write_protection(Protection::Enable)
+        init_mode(&self.rtc, function)
     }
 
     pub fn get_wakeup_manager(&mut self) -> WakeupManager {
         WakeupManager::new(self)
     }
-    fn initf(&mut self, init: Init) {
-        match init {
-            Init::Start => {
-                if self.rtc.isr.read().init().bit_is_clear() {
-                    self.rtc.isr.modify(|_, w| w.init().set_bit());
-                    while self.rtc.isr.read().initf().bit_is_clear() {}
-                }
-            }
-            Init::Stop => {
-                if !self.rtc.isr.read().init().bit_is_clear() {
-                    self.rtc.isr.modify(|_, w| w.init().clear_bit());
-                    while !self.rtc.isr.read().initf().bit_is_clear() {}
-                }
-            }
-        }
-    }
-
-    /// Enable/Disable write protection for RTC module
-    pub(crate) fn write_protection(&self, protection: Protection) {
-        match protection {
-            Protection::Disable => {
-                self.rtc.wpr.write(|w| w.key().bits(self.unlock_key.0));
-                self.rtc.wpr.write(|w| w.key().bits(self.unlock_key.1))
-            }
-            Protection::Enable => self.rtc.wpr.write(|w| w.key().bits(0xC0)),
-        }
-    }
 }
 
 impl RtcSetup<Rtc> for Rtc {
@@ -298,23 +503,9 @@ impl RtcSetup<Rtc> for Rtc {
 }
 
 impl TimeAccess for Rtc {
-    /// Returns current time as Time struct
+    /// Returns current time as Time struct, in 24 hour format
     fn time(&self) -> Time {
-        BcdTime {
-            hour: Bcd {
-                tens: self.rtc.tr.read().ht().bits(),
-                units: self.rtc.tr.read().hu().bits(),
-            },
-            minutes: Bcd {
-                tens: self.rtc.tr.read().mnt().bits(),
-                units: self.rtc.tr.read().mnu().bits(),
-            },
-            seconds: Bcd {
-                tens: self.rtc.tr.read().st().bits(),
-                units: self.rtc.tr.read().su().bits(),
-            },
-        }
-        .time()
+        read_calendar(&self.rtc).1
     }
 
     /// Set time by Time struct
@@ -322,60 +513,174 @@ impl TimeAccess for Rtc {
     /// rtc.set_time(Time::from(12,30,0));
     /// ```
     fn set_time(&mut self, time: Time) {
-        let bcd_time = BcdTime::from(time);
-        self.modify(|rtc| {
-            rtc.tr.modify(|_, w| {
-                w.ht().bits(bcd_time.hour.tens);
-                w.hu().bits(bcd_time.hour.units);
-                w.mnt().bits(bcd_time.minutes.tens);
-                w.mnu().bits(bcd_time.minutes.units);
-                w.st().bits(bcd_time.seconds.tens);
-                w.su().bits(bcd_time.seconds.units)
-            })
-        })
+        self.modify(|rtc| write_time(rtc, &time))
     }
 }
 
 impl DateAccess for Rtc {
     /// Returns current date as Date struct
     fn date(&self) -> Date {
-        BcdDate {
-            d: Bcd {
-                tens: self.rtc.dr.read().dt().bits(),
-                units: self.rtc.dr.read().du().bits(),
-            },
-            m: Bcd {
-                tens: u8::from(self.rtc.dr.read().mt().bit()),
-                units: self.rtc.dr.read().mu().bits(),
-            },
-            y: Bcd {
-                tens: self.rtc.dr.read().yt().bits(),
-                units: self.rtc.dr.read().yu().bits(),
-            },
-        }
-        .date()
+        read_calendar(&self.rtc).0
     }
 
-    /// Set date with Date struct, It takes year between 2000 and 2154,
-    /// if you will pick some other year it is going to reset it to 2000
+    /// Set date with Date struct, year has to be in range given by **Rtc::year_range()**
+    /// (2000 - 2099 by default), dates with other years are ignored. Please use
+    /// **Rtc::set_date_time()** to get an error for them.
     /// ```
     /// rtc.set_date(Date::from(1,1,2024));
     /// ```
     fn set_date(&mut self, date: Date) {
-        let bcd_date = BcdDate::from(date);
-        self.modify(|rtc| {
-            rtc.dr.modify(|_, w| {
-                match bcd_date.m.tens > 0 {
-                    true => w.mt().bit(true),
-                    false => w.mt().bit(false),
-                };
-                w.dt().bits(bcd_date.d.tens);
-                w.du().bits(bcd_date.d.units);
-                w.mu().bits(bcd_date.m.units);
-                w.yt().bits(bcd_date.y.tens);
-                w.yu().bits(bcd_date.y.units)
-            })
-        })
+        if let Ok(date) = century::store(&self.rtc, &date) {
+            self.modify(|rtc| write_date(rtc, &BcdDate::from(date)))
+        }
+    }
+}
+
+/// Writes time into time register in the hour format that RTC uses,
+/// RTC has to be in init mode
+pub(crate) fn write_time(rtc: &RegisterBlock, time: &Time) {
+    let (hour, pm) = match rtc.cr.read().fmt().bit_is_set() {
+        true => {
+            let time: Time12 = (*time).into();
+            (time.hour, time.am_pm == AmPm::PM)
+        }
+        false => (time.hour, false),
+    };
+    let bcd_time = BcdTime::from(Time::from(hour, time.minute, time.second));
+    rtc.tr.modify(|_, w| {
+        w.pm().bit(pm);
+        w.ht().bits(bcd_time.hour.tens);
+        w.hu().bits(bcd_time.hour.units);
+        w.mnt().bits(bcd_time.minutes.tens);
+        w.mnu().bits(bcd_time.minutes.units);
+        w.st().bits(bcd_time.seconds.tens);
+        w.su().bits(bcd_time.seconds.units)
+    })
+}
+
+/// Writes BCD date into date register, RTC has to be in init mode
+pub(crate) fn write_date(rtc: &RegisterBlock, bcd_date: &BcdDate) {
+    rtc.dr.modify(|_, w| {
+        match bcd_date.m.tens > 0 {
+            true => w.mt().bit(true),
+            false => w.mt().bit(false),
+        };
+        w.dt().bits(bcd_date.d.tens);
+        w.du().bits(bcd_date.d.units);
+        w.mu().bits(bcd_date.m.units);
+        w.yt().bits(bcd_date.y.tens);
+        w.yu().bits(bcd_date.y.units)
+    })
+}
+
+/// Writes date, weekday and time at once. Fraction of second is applied by shifting sub second
+/// counter after init mode
+pub(crate) fn write_date_time(
+    rtc: &RegisterBlock,
+    date_time: &DateTime,
+) -> Result<(), ConversionError> {
+    let bcd_date = BcdDate::from(century::store(rtc, &date_time.date)?);
+    let weekday = date_time.date.weekday().number_from_monday();
+    init_mode(rtc, |rtc| {
+        write_date(rtc, &bcd_date);
+        // Weekday is always in 1 - 7 range
+        rtc.dr.modify(|_, w| unsafe { w.wdu().bits(weekday) });
+        write_time(rtc, &date_time.time)
+    });
+    shift_sub_second(rtc, date_time.nanosecond);
+    Ok(())
+}
+
+/// Moves clock forward by a fraction of second using shift control register
+fn shift_sub_second(rtc: &RegisterBlock, nanosecond: u32) {
+    let prediv_s = u64::from(rtc.prer.read().prediv_s().bits());
+    let advance = u64::from(nanosecond.min(999_999_999)) * (prediv_s + 1) / 1_000_000_000;
+    if advance == 0 {
+        return;
+    }
+    unprotected(rtc, |rtc| {
+        while rtc.isr.read().shpf().bit_is_set() {}
+        // Adding one second and subtracting rest of it moves clock by advance ticks
+        rtc.shiftr.write(|w| {
+            w.add1s().set_bit();
+            w.subfs().bits((prediv_s + 1 - advance) as u16)
+        });
+        wakeup::calendar_changed()
+    })
+}
+
+/// Reads calendar at once, fraction of second is calculated from sub second register
+pub(crate) fn read_date_time(rtc: &RegisterBlock) -> DateTime {
+    let (date, time, ssr) = read_calendar(rtc);
+    let prediv_s = u64::from(rtc.prer.read().prediv_s().bits());
+    let sub_second = prediv_s.saturating_sub(u64::from(ssr));
+    DateTime {
+        date,
+        time,
+        nanosecond: (sub_second * 1_000_000_000 / (prediv_s + 1)) as u32,
+    }
+}
+
+/// Reads calendar at once and returns date, time (in 24 hour format) and sub second register.
+/// Reading sub second register first locks time and date shadow registers until date register
+/// is read, so it is done in critical section to keep values coherent also when calendar is
+/// read from interrupts. All calendar reads of the crate go through it.
+pub(crate) fn read_calendar(rtc: &RegisterBlock) -> (Date, Time, u16) {
+    let (ssr, tr, dr) = free(|_| (rtc.ssr.read().ss().bits(), rtc.tr.read(), rtc.dr.read()));
+    let time = time_from_register(&tr, rtc.cr.read().fmt().bit_is_set());
+    (date_from_register(rtc, &dr), time, ssr)
+}
+
+/// Decodes date register, full year is given by century tracker
+pub(crate) fn date_from_register(rtc: &RegisterBlock, dr: &dr::R) -> Date {
+    century::full_date(rtc, short_date_from_register(dr))
+}
+
+/// Decodes date register with two digit year
+pub(crate) fn short_date_from_register(dr: &dr::R) -> Date {
+    BcdDate {
+        d: Bcd {
+            tens: dr.dt().bits(),
+            units: dr.du().bits(),
+        },
+        m: Bcd {
+            tens: u8::from(dr.mt().bit()),
+            units: dr.mu().bits(),
+        },
+        y: Bcd {
+            tens: dr.yt().bits(),
+            units: dr.yu().bits(),
+        },
+    }
+    .date()
+}
+
+/// Decodes time register into 24 hour time
+pub(crate) fn time_from_register(tr: &tr::R, h12: bool) -> Time {
+    let time = BcdTime {
+        hour: Bcd {
+            tens: tr.ht().bits(),
+            units: tr.hu().bits(),
+        },
+        minutes: Bcd {
+            tens: tr.mnt().bits(),
+            units: tr.mnu().bits(),
+        },
+        seconds: Bcd {
+            tens: tr.st().bits(),
+            units: tr.su().bits(),
+        },
+    }
+    .time();
+    match h12 {
+        true => {
+            let am_pm = match tr.pm().bit_is_set() {
+                true => AmPm::PM,
+                false => AmPm::AM,
+            };
+            Time12::from(time.hour, time.minute, time.second, am_pm).into()
+        }
+        false => time,
     }
 }
 
@@ -0,0 +1,232 @@
+//! Implementation of community [rtcc](https://docs.rs/rtcc) traits for [Rtc], so code written
+//! against **DateTimeAccess** and **Rtcc** can use MCU RTC the same way as external RTC chips.
+//!
+//! ## Usage:
+//! ```
+//! use rtcc::{DateTimeAccess, NaiveDate};
+//! use stm32f3_rtc::rtc::Rtc;
+//! use stm32f3xx_hal::pac;
+//!
+//! let mut peripheral = pac::Peripherals::take().unwrap();
+//! let mut rtc = Rtc::new(peripheral.RTC);
+//! rtc.start_clock(&mut peripheral.PWR, &mut peripheral.RCC);
+//! let datetime = NaiveDate::from_ymd_opt(2024, 6, 1)
+//!     .unwrap()
+//!     .and_hms_opt(12, 30, 0)
+//!     .unwrap();
+//! rtc.set_datetime(&datetime).unwrap();
+//! ```
+use century;
+use datetime::{AmPm, BcdDate, Date, DateAccess, DateTime, Time, Time12, TimeAccess};
+use rtc::{read_calendar, write_date, write_date_time, HourFormat, Rtc};
+use rtcc::{DateTimeAccess, Datelike, Hours, NaiveDate, NaiveDateTime, NaiveTime, Rtcc, Timelike};
+
+/// Errors returned by rtcc traits implementation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// Value is out of range that RTC can keep (e.g. year out of **Rtc::year_range()**)
+    InvalidInputData,
+}
+
+/// Converts date read from RTC into NaiveDate
+fn naive_date(date: &Date) -> Result<NaiveDate, Error> {
+    NaiveDate::from_ymd_opt(date.year as i32, u32::from(date.month), u32::from(date.day))
+        .ok_or(Error::InvalidInputData)
+}
+
+/// Converts 24 hour time read from RTC into NaiveTime
+fn naive_time(time: &Time) -> Result<NaiveTime, Error> {
+    NaiveTime::from_hms_opt(
+        u32::from(time.hour),
+        u32::from(time.minute),
+        u32::from(time.second),
+    )
+    .ok_or(Error::InvalidInputData)
+}
+
+/// Returns time, when hour, minute and second are in range
+fn checked_time(hour: u8, minute: u8, second: u8) -> Result<Time, Error> {
+    match hour < 24 && minute < 60 && second < 60 {
+        true => Ok(Time::from(hour, minute, second)),
+        false => Err(Error::InvalidInputData),
+    }
+}
+
+/// Returns date with full year, when year is in range that calendar can keep
+fn checked_date(date: &NaiveDate) -> Result<Date, Error> {
+    let year = century::check_year(i64::from(date.year())).map_err(|_| Error::InvalidInputData)?;
+    Ok(Date::from(date.day() as u8, date.month() as u8, year))
+}
+
+impl Rtc {
+    /// Returns hours in the format that RTC currently uses
+    fn read_hours(&self) -> Hours {
+        let (_, time, _) = read_calendar(&self.rtc);
+        match self.hour_format() {
+            HourFormat::H24 => Hours::H24(time.hour),
+            HourFormat::H12 => {
+                let time: Time12 = time.into();
+                match time.am_pm {
+                    AmPm::AM => Hours::AM(time.hour),
+                    AmPm::PM => Hours::PM(time.hour),
+                }
+            }
+        }
+    }
+
+    /// Writes time into RTC, **Hours::AM** and **Hours::PM** switch RTC into 12 hour format,
+    /// **Hours::H24** switches it into 24 hour format (through **set_hour_format()**)
+    fn write_time(&mut self, hours: Hours, minute: u8, second: u8) -> Result<(), Error> {
+        let (hour_format, hour) = match hours {
+            Hours::AM(hour) if (1..=12).contains(&hour) => (HourFormat::H12, hour % 12),
+            Hours::PM(hour) if (1..=12).contains(&hour) => (HourFormat::H12, hour % 12 + 12),
+            Hours::H24(hour) => (HourFormat::H24, hour),
+            _ => return Err(Error::InvalidInputData),
+        };
+        let time = checked_time(hour, minute, second)?;
+        if self.hour_format() != hour_format {
+            self.set_hour_format(hour_format);
+        }
+        TimeAccess::set_time(self, time);
+        Ok(())
+    }
+
+    /// Writes date with weekday (1 - Monday ... 7 - Sunday), century is kept by century
+    /// tracker
+    fn write_date(&mut self, date: &Date, weekday: u8) -> Result<(), Error> {
+        if !(1..=7).contains(&weekday) {
+            return Err(Error::InvalidInputData);
+        }
+        let date = century::store(&self.rtc, date).map_err(|_| Error::InvalidInputData)?;
+        let bcd_date = BcdDate::from(date);
+        self.modify(|rtc| {
+            write_date(rtc, &bcd_date);
+            // Weekday is already checked to be in 1 - 7 range
+            rtc.dr.modify(|_, w| unsafe { w.wdu().bits(weekday) })
+        });
+        Ok(())
+    }
+
+    fn write_naive_date(&mut self, date: &NaiveDate) -> Result<(), Error> {
+        self.write_date(
+            &checked_date(date)?,
+            date.weekday().number_from_monday() as u8,
+        )
+    }
+}
+
+impl DateTimeAccess for Rtc {
+    type Error = Error;
+
+    /// Returns date and time read at once, so they are coherent also around midnight
+    fn datetime(&mut self) -> Result<NaiveDateTime, Self::Error> {
+        let (date, time, _) = read_calendar(&self.rtc);
+        Ok(naive_date(&date)?.and_time(naive_time(&time)?))
+    }
+
+    /// Writes date and time in one init mode, so calendar is not read or rolled over between
+    /// them. Nothing is written when any of them is out of range.
+    fn set_datetime(&mut self, datetime: &NaiveDateTime) -> Result<(), Self::Error> {
+        let time = checked_time(
+            datetime.hour() as u8,
+            datetime.minute() as u8,
+            datetime.second() as u8,
+        )?;
+        let date_time = DateTime::from(checked_date(&datetime.date())?, time, 0);
+        write_date_time(&self.rtc, &date_time).map_err(|_| Error::InvalidInputData)
+    }
+}
+
+impl Rtcc for Rtc {
+    fn seconds(&mut self) -> Result<u8, Self::Error> {
+        Ok(TimeAccess::time(self).second)
+    }
+
+    fn minutes(&mut self) -> Result<u8, Self::Error> {
+        Ok(TimeAccess::time(self).minute)
+    }
+
+    fn hours(&mut self) -> Result<Hours, Self::Error> {
+        Ok(self.read_hours())
+    }
+
+    fn time(&mut self) -> Result<NaiveTime, Self::Error> {
+        let (_, time, _) = read_calendar(&self.rtc);
+        naive_time(&time)
+    }
+
+    fn weekday(&mut self) -> Result<u8, Self::Error> {
+        Ok(self.rtc.dr.read().wdu().bits())
+    }
+
+    fn day(&mut self) -> Result<u8, Self::Error> {
+        Ok(DateAccess::date(self).day)
+    }
+
+    fn month(&mut self) -> Result<u8, Self::Error> {
+        Ok(DateAccess::date(self).month)
+    }
+
+    fn year(&mut self) -> Result<u16, Self::Error> {
+        Ok(DateAccess::date(self).year as u16)
+    }
+
+    fn date(&mut self) -> Result<NaiveDate, Self::Error> {
+        naive_date(&DateAccess::date(self))
+    }
+
+    fn set_seconds(&mut self, seconds: u8) -> Result<(), Self::Error> {
+        let time = TimeAccess::time(self);
+        self.write_time(self.read_hours(), time.minute, seconds)
+    }
+
+    fn set_minutes(&mut self, minutes: u8) -> Result<(), Self::Error> {
+        let time = TimeAccess::time(self);
+        self.write_time(self.read_hours(), minutes, time.second)
+    }
+
+    /// Sets hours, **Hours::AM** and **Hours::PM** switch RTC into 12 hour format,
+    /// **Hours::H24** switches it into 24 hour format
+    fn set_hours(&mut self, hours: Hours) -> Result<(), Self::Error> {
+        let time = TimeAccess::time(self);
+        self.write_time(hours, time.minute, time.second)
+    }
+
+    fn set_time(&mut self, time: &NaiveTime) -> Result<(), Self::Error> {
+        let time = checked_time(time.hour() as u8, time.minute() as u8, time.second() as u8)?;
+        TimeAccess::set_time(self, time);
+        Ok(())
+    }
+
+    /// Sets weekday, 1 - Monday ... 7 - Sunday
+    fn set_weekday(&mut self, weekday: u8) -> Result<(), Self::Error> {
+        let date = DateAccess::date(self);
+        self.write_date(&date, weekday)
+    }
+
+    fn set_day(&mut self, day: u8) -> Result<(), Self::Error> {
+        let date = DateAccess::date(self);
+        let date = NaiveDate::from_ymd_opt(date.year as i32, u32::from(date.month), u32::from(day))
+            .ok_or(Error::InvalidInputData)?;
+        self.write_naive_date(&date)
+    }
+
+    fn set_month(&mut self, month: u8) -> Result<(), Self::Error> {
+        let date = DateAccess::date(self);
+        let date = NaiveDate::from_ymd_opt(date.year as i32, u32::from(month), u32::from(date.day))
+            .ok_or(Error::InvalidInputData)?;
+        self.write_naive_date(&date)
+    }
+
+    fn set_year(&mut self, year: u16) -> Result<(), Self::Error> {
+        let date = DateAccess::date(self);
+        let date =
+            NaiveDate::from_ymd_opt(i32::from(year), u32::from(date.month), u32::from(date.day))
+                .ok_or(Error::InvalidInputData)?;
+        self.write_naive_date(&date)
+    }
+
+    fn set_date(&mut self, date: &NaiveDate) -> Result<(), Self::Error> {
+        self.write_naive_date(date)
+    }
+}
@@ -0,0 +1,227 @@
+//! [serde](https://docs.rs/serde) support for [datetime](crate::datetime) types.
+//!
+//! Human readable formats (e.g. JSON) keep values as ISO 8601 strings:
+//! - Date: `"2024-06-01"`
+//! - Time: `"12:30:00"`
+//! - DateTime: `"2024-06-01T12:30:00.125"`, fraction of second is written with 3, 6 or 9
+//!   digits, so no precision is lost
+//!
+//! Binary formats (e.g. postcard) keep values as compact tuples of u32 numbers:
+//! - Date: `(year, month, day)`
+//! - Time: `(hour, minute, second)`
+//! - DateTime: `(year, month, day, hour, minute, second, nanosecond)`
+//!
+//! ## Usage:
+//! ```
+//! use stm32f3_rtc::datetime::{Date, DateTime, Time};
+//!
+//! let date_time = DateTime::from(Date::from(1, 6, 2024), Time::from(12, 30, 0), 0);
+//! let mut buffer = [0u8; 32];
+//! let length = serde_json_core::to_slice(&date_time, &mut buffer).unwrap();
+//! assert_eq!(&buffer[..length], b"\"2024-06-01T12:30:00\"");
+//!
+//! let date_time = DateTime::from(Date::from(1, 6, 2024), Time::from(12, 30, 0), 125_000_001);
+//! let length = serde_json_core::to_slice(&date_time, &mut buffer).unwrap();
+//! assert_eq!(&buffer[..length], b"\"2024-06-01T12:30:00.125000001\"");
+//! let (parsed, _): (DateTime, _) = serde_json_core::from_slice(&buffer[..length]).unwrap();
+//! assert_eq!(parsed, date_time);
+//!
+//! let (date, _): (Date, _) = serde_json_core::from_str("\"2024-06-01\"").unwrap();
+//! assert_eq!(date, Date::from(1, 6, 2024));
+//! assert!(serde_json_core::from_str::<Time>("\"24:00:00\"").is_err());
+//! ```
+use core::fmt;
+use datetime::{Date, DateTime, Time};
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeTuple, Serializer};
+
+impl Serialize for Date {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            return serializer.collect_str(self);
+        }
+        let mut tuple = serializer.serialize_tuple(3)?;
+        tuple.serialize_element(&self.year)?;
+        tuple.serialize_element(&u32::from(self.month))?;
+        tuple.serialize_element(&u32::from(self.day))?;
+        tuple.end()
+    }
+}
+
+impl Serialize for Time {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            return serializer.collect_str(self);
+        }
+        let mut tuple = serializer.serialize_tuple(3)?;
+        tuple.serialize_element(&u32::from(self.hour))?;
+        tuple.serialize_element(&u32::from(self.minute))?;
+        tuple.serialize_element(&u32::from(self.second))?;
+        tuple.end()
+    }
+}
+
+/// Formats date and time with the shortest of 3, 6 or 9 fraction digits, that keeps whole
+/// fraction of second
+struct FullFraction<'a>(&'a DateTime);
+
+impl<'a> fmt::Display for FullFraction<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = match self.0.nanosecond {
+            0 => 0,
+            nanosecond if nanosecond % 1_000_000 == 0 => 3,
+            nanosecond if nanosecond % 1_000 == 0 => 6,
+            _ => 9,
+        };
+        self.0.write_iso8601(f, digits)
+    }
+}
+
+impl Serialize for DateTime {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            return serializer.collect_str(&FullFraction(self));
+        }
+        let mut tuple = serializer.serialize_tuple(7)?;
+        tuple.serialize_element(&self.date.year)?;
+        tuple.serialize_element(&u32::from(self.date.month))?;
+        tuple.serialize_element(&u32::from(self.date.day))?;
+        tuple.serialize_element(&u32::from(self.time.hour))?;
+        tuple.serialize_element(&u32::from(self.time.minute))?;
+        tuple.serialize_element(&u32::from(self.time.second))?;
+        tuple.serialize_element(&self.nanosecond)?;
+        tuple.end()
+    }
+}
+
+/// Reads value from string, it is implemented by every datetime type
+trait FromIso: Sized {
+    /// Expected format used in error messages
+    const FORMAT: &'static str;
+
+    fn from_iso(text: &str) -> Option<Self>;
+
+    /// Checks if all fields are in their range
+    fn is_valid(&self) -> bool;
+}
+
+impl FromIso for Date {
+    const FORMAT: &'static str = "date in YYYY-MM-DD format";
+
+    fn from_iso(text: &str) -> Option<Self> {
+        Date::parse_iso8601(text).ok()
+    }
+
+    fn is_valid(&self) -> bool {
+        (1..=Date::days_in_month(self.year, self.month)).contains(&self.day)
+    }
+}
+
+impl FromIso for Time {
+    const FORMAT: &'static str = "time in hh:mm:ss format";
+
+    fn from_iso(text: &str) -> Option<Self> {
+        Time::parse_iso8601(text).ok()
+    }
+
+    fn is_valid(&self) -> bool {
+        self.hour < 24 && self.minute < 60 && self.second < 60
+    }
+}
+
+impl FromIso for DateTime {
+    const FORMAT: &'static str = "date and time in YYYY-MM-DDThh:mm:ss[.fffffffff] format";
+
+    fn from_iso(text: &str) -> Option<Self> {
+        DateTime::parse_iso8601(text).ok()
+    }
+
+    fn is_valid(&self) -> bool {
+        self.date.is_valid() && self.time.is_valid() && self.nanosecond < 1_000_000_000
+    }
+}
+
+/// Visitor that reads datetime types from ISO 8601 string or tuple of numbers
+struct DateTimeVisitor<T> {
+    length: usize,
+    from_seq: fn(&[u32]) -> Option<T>,
+}
+
+impl<'de, T: FromIso> Visitor<'de> for DateTimeVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(T::FORMAT)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<T, E> {
+        T::from_iso(value)
+            .filter(T::is_valid)
+            .ok_or_else(|| E::invalid_value(de::Unexpected::Str(value), &self))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<T, A::Error> {
+        let mut values = [0u32; 7];
+        for (index, value) in values.iter_mut().take(self.length).enumerate() {
+            *value = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(index, &self))?;
+        }
+        (self.from_seq)(&values)
+            .filter(T::is_valid)
+            .ok_or_else(|| de::Error::custom("datetime value out of range"))
+    }
+}
+
+/// Deserializes ISO 8601 string or tuple depending on data format
+fn deserialize<'de, D: Deserializer<'de>, T: FromIso>(
+    deserializer: D,
+    length: usize,
+    from_seq: fn(&[u32]) -> Option<T>,
+) -> Result<T, D::Error> {
+    let visitor = DateTimeVisitor { length, from_seq };
+    match deserializer.is_human_readable() {
+        true => deserializer.deserialize_str(visitor),
+        false => deserializer.deserialize_tuple(length, visitor),
+    }
+}
+
+/// Converts number read from tuple into u8, values out of range are rejected
+fn small(value: u32) -> Option<u8> {
+    match value <= u32::from(u8::MAX) {
+        true => Some(value as u8),
+        false => None,
+    }
+}
+
+impl<'de> Deserialize<'de> for Date {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize(deserializer, 3, |values| {
+            Some(Date::from(small(values[2])?, small(values[1])?, values[0]))
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for Time {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize(deserializer, 3, |values| {
+            Some(Time::from(
+                small(values[0])?,
+                small(values[1])?,
+                small(values[2])?,
+            ))
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for DateTime {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize(deserializer, 7, |values| {
+            Some(DateTime::from(
+                Date::from(small(values[2])?, small(values[1])?, values[0]),
+                Time::from(small(values[3])?, small(values[4])?, small(values[5])?),
+                values[6],
+            ))
+        })
+    }
+}
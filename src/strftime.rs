@@ -0,0 +1,445 @@
+//! strftime-like formatting for [datetime](crate::datetime) types into any [fmt::Write],
+//! without allocation.
+//!
+//! Supported specifiers:
+//!
+//! | Specifier | Meaning | Example |
+//! |-----------|---------|---------|
+//! | `%Y` | Year | `2024` |
+//! | `%C` | Century (year / 100) | `20` |
+//! | `%y` | Year without century | `24` |
+//! | `%m` | Month | `06` |
+//! | `%b`, `%h` | Short month name | `Jun` |
+//! | `%B` | Month name | `June` |
+//! | `%d` | Day of the month | `01` |
+//! | `%e` | Day of the month padded with space | ` 1` |
+//! | `%j` | Day of the year | `153` |
+//! | `%a` | Short weekday name | `Sat` |
+//! | `%A` | Weekday name | `Saturday` |
+//! | `%u` | Weekday, 1 - Monday ... 7 - Sunday | `6` |
+//! | `%w` | Weekday, 0 - Sunday ... 6 - Saturday | `6` |
+//! | `%D` | Same as `%m/%d/%y` | `06/01/24` |
+//! | `%F` | Same as `%Y-%m-%d` | `2024-06-01` |
+//! | `%H` | Hour (00 - 23) | `07` |
+//! | `%k` | Hour (0 - 23) padded with space | ` 7` |
+//! | `%I` | Hour (01 - 12) | `07` |
+//! | `%l` | Hour (1 - 12) padded with space | ` 7` |
+//! | `%p` | `AM` or `PM` | `AM` |
+//! | `%M` | Minute | `05` |
+//! | `%S` | Second | `09` |
+//! | `%f` | Nanoseconds (9 digits) | `250000000` |
+//! | `%1f` ... `%9f` | Fraction of second with given amount of digits | `250` |
+//! | `%T` | Same as `%H:%M:%S` | `07:05:09` |
+//! | `%R` | Same as `%H:%M` | `07:05` |
+//! | `%n`, `%t`, `%%` | New line, tab and `%` | |
+//!
+//! Spec can be checked at compile time with [validate], so runtime formatting fails only
+//! for missing fields (e.g. `%H` used with **Date**) or writer errors.
+//!
+//! ## Usage:
+//! ```
+//! use stm32f3_rtc::datetime::{Date, DateTime, Time};
+//! use stm32f3_rtc::strftime;
+//!
+//! const LCD_FORMAT: &str = "%d.%m.%Y %H:%M";
+//! const _: () = assert!(strftime::validate(LCD_FORMAT).is_ok());
+//!
+//! let date_time = DateTime::from(Date::from(1, 6, 2024), Time::from(7, 5, 9), 0);
+//! let mut text = String::new();
+//! date_time.strftime(&mut text, LCD_FORMAT).unwrap();
+//! assert_eq!(text, "01.06.2024 07:05");
+//!
+//! text.clear();
+//! date_time.date.strftime(&mut text, "%a %b %e").unwrap();
+//! assert_eq!(text, "Sat Jun  1");
+//! ```
+use core::fmt;
+use datetime::{Date, DateTime, Time};
+
+/// Errors returned by strftime formatting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatError {
+    /// Unknown specifier at given byte position of the spec
+    UnsupportedSpecifier(usize),
+    /// Spec ends with single `%`
+    Incomplete,
+    /// Specifier at given byte position needs field that formatted value does not have
+    /// (e.g. `%H` used for **Date**)
+    MissingField(usize),
+    /// Writer returned error (e.g. buffer is full)
+    Write,
+}
+
+impl From<fmt::Error> for FormatError {
+    fn from(_: fmt::Error) -> Self {
+        FormatError::Write
+    }
+}
+
+/// Single conversion specifier
+#[derive(Clone, Copy)]
+enum Specifier {
+    Year,
+    Century,
+    ShortYear,
+    Month,
+    ShortMonthName,
+    MonthName,
+    Day,
+    SpaceDay,
+    DayOfYear,
+    ShortWeekdayName,
+    WeekdayName,
+    WeekdayFromMonday,
+    WeekdayFromSunday,
+    UsDate,
+    IsoDate,
+    Hour,
+    SpaceHour,
+    Hour12,
+    SpaceHour12,
+    AmPm,
+    Minute,
+    Second,
+    Fraction(u8),
+    IsoTime,
+    ShortTime,
+    Text(&'static str),
+}
+
+/// Fields required by the specifier
+enum Requires {
+    Nothing,
+    Date,
+    Time,
+    Fraction,
+}
+
+impl Specifier {
+    fn requires(self) -> Requires {
+        match self {
+            Specifier::Hour
+            | Specifier::SpaceHour
+            | Specifier::Hour12
+            | Specifier::SpaceHour12
+            | Specifier::AmPm
+            | Specifier::Minute
+            | Specifier::Second
+            | Specifier::IsoTime
+            | Specifier::ShortTime => Requires::Time,
+            Specifier::Fraction(_) => Requires::Fraction,
+            Specifier::Text(_) => Requires::Nothing,
+            _ => Requires::Date,
+        }
+    }
+}
+
+/// Reads specifier that starts at given position (just after `%`).
+/// Returns specifier together with its length.
+const fn specifier(spec: &[u8], position: usize) -> Result<(Specifier, usize), FormatError> {
+    if position >= spec.len() {
+        return Err(FormatError::Incomplete);
+    }
+    let specifier = match spec[position] {
+        b'Y' => Specifier::Year,
+        b'C' => Specifier::Century,
+        b'y' => Specifier::ShortYear,
+        b'm' => Specifier::Month,
+        b'b' | b'h' => Specifier::ShortMonthName,
+        b'B' => Specifier::MonthName,
+        b'd' => Specifier::Day,
+        b'e' => Specifier::SpaceDay,
+        b'j' => Specifier::DayOfYear,
+        b'a' => Specifier::ShortWeekdayName,
+        b'A' => Specifier::WeekdayName,
+        b'u' => Specifier::WeekdayFromMonday,
+        b'w' => Specifier::WeekdayFromSunday,
+        b'D' => Specifier::UsDate,
+        b'F' => Specifier::IsoDate,
+        b'H' => Specifier::Hour,
+        b'k' => Specifier::SpaceHour,
+        b'I' => Specifier::Hour12,
+        b'l' => Specifier::SpaceHour12,
+        b'p' => Specifier::AmPm,
+        b'M' => Specifier::Minute,
+        b'S' => Specifier::Second,
+        b'f' => Specifier::Fraction(9),
+        b'T' => Specifier::IsoTime,
+        b'R' => Specifier::ShortTime,
+        b'n' => Specifier::Text("\n"),
+        b't' => Specifier::Text("\t"),
+        b'%' => Specifier::Text("%"),
+        digits @ b'1'..=b'9' if position + 1 < spec.len() && spec[position + 1] == b'f' => {
+            return Ok((Specifier::Fraction(digits - b'0'), 2));
+        }
+        _ => return Err(FormatError::UnsupportedSpecifier(position - 1)),
+    };
+    Ok((specifier, 1))
+}
+
+/// Checks that every specifier of the spec is supported. It is `const`, so spec can be
+/// checked during compilation.
+/// ```
+/// use stm32f3_rtc::strftime::{validate, FormatError};
+/// assert_eq!(validate("%H:%M:%3f"), Ok(()));
+/// assert_eq!(validate("%d.%m.%Q"), Err(FormatError::UnsupportedSpecifier(6)));
+/// assert_eq!(validate("100%"), Err(FormatError::Incomplete));
+/// ```
+pub const fn validate(spec: &str) -> Result<(), FormatError> {
+    let spec = spec.as_bytes();
+    let mut position = 0;
+    while position < spec.len() {
+        if spec[position] == b'%' {
+            match specifier(spec, position + 1) {
+                Ok((_, length)) => position += length,
+                Err(error) => return Err(error),
+            }
+        }
+        position += 1;
+    }
+    Ok(())
+}
+
+const MONTH_NAMES: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// Fields of formatted value, missing ones are `None`
+struct Fields {
+    date: Option<Date>,
+    time: Option<Time>,
+    nanosecond: Option<u32>,
+}
+
+impl Fields {
+    fn has(&self, requires: Requires) -> bool {
+        match requires {
+            Requires::Nothing => true,
+            Requires::Date => self.date.is_some(),
+            Requires::Time => self.time.is_some(),
+            Requires::Fraction => self.nanosecond.is_some(),
+        }
+    }
+
+    /// Checks whole spec, so nothing is written when it cannot be formatted
+    fn check(&self, spec: &str) -> Result<(), FormatError> {
+        let bytes = spec.as_bytes();
+        let mut position = 0;
+        while position < bytes.len() {
+            if bytes[position] == b'%' {
+                let (specifier, length) = specifier(bytes, position + 1)?;
+                if !self.has(specifier.requires()) {
+                    return Err(FormatError::MissingField(position));
+                }
+                position += length;
+            }
+            position += 1;
+        }
+        Ok(())
+    }
+
+    fn write<W: fmt::Write>(&self, writer: &mut W, spec: &str) -> Result<(), FormatError> {
+        self.check(spec)?;
+        let bytes = spec.as_bytes();
+        let mut literal = 0;
+        let mut position = 0;
+        while position < bytes.len() {
+            if bytes[position] == b'%' {
+                writer.write_str(&spec[literal..position])?;
+                let (specifier, length) = specifier(bytes, position + 1)?;
+                self.write_specifier(writer, specifier)?;
+                position += length;
+                literal = position + 1;
+            }
+            position += 1;
+        }
+        writer.write_str(&spec[literal..])?;
+        Ok(())
+    }
+
+    /// Writes single specifier, its fields are already checked
+    fn write_specifier<W: fmt::Write>(&self, writer: &mut W, specifier: Specifier) -> fmt::Result {
+        let date = self.date.unwrap_or_else(|| Date::from(1, 1, 2000));
+        let time = self.time.unwrap_or_else(|| Time::from(0, 0, 0));
+        let month_name = MONTH_NAMES[usize::from(date.month.clamp(1, 12) - 1)];
+        let hour_12 = match time.hour % 12 {
+            0 => 12,
+            hour => hour,
+        };
+        match specifier {
+            Specifier::Year => write!(writer, "{:04}", date.year),
+            Specifier::Century => write!(writer, "{:02}", date.year / 100),
+            Specifier::ShortYear => write!(writer, "{:02}", date.year % 100),
+            Specifier::Month => write!(writer, "{:02}", date.month),
+            Specifier::ShortMonthName => writer.write_str(&month_name[..3]),
+            Specifier::MonthName => writer.write_str(month_name),
+            Specifier::Day => write!(writer, "{:02}", date.day),
+            Specifier::SpaceDay => write!(writer, "{:2}", date.day),
+            Specifier::DayOfYear => write!(writer, "{:03}", date.day_of_year()),
+            Specifier::ShortWeekdayName => writer.write_str(&date.weekday().name()[..3]),
+            Specifier::WeekdayName => writer.write_str(date.weekday().name()),
+            Specifier::WeekdayFromMonday => {
+                write!(writer, "{}", date.weekday().number_from_monday())
+            }
+            Specifier::WeekdayFromSunday => write!(writer, "{}", date.weekday().days_from_sunday()),
+            Specifier::UsDate => write!(
+                writer,
+                "{:02}/{:02}/{:02}",
+                date.month,
+                date.day,
+                date.year % 100
+            ),
+            Specifier::IsoDate => write!(writer, "{}", date),
+            Specifier::Hour => write!(writer, "{:02}", time.hour),
+            Specifier::SpaceHour => write!(writer, "{:2}", time.hour),
+            Specifier::Hour12 => write!(writer, "{:02}", hour_12),
+            Specifier::SpaceHour12 => write!(writer, "{:2}", hour_12),
+            Specifier::AmPm => writer.write_str(if time.hour < 12 { "AM" } else { "PM" }),
+            Specifier::Minute => write!(writer, "{:02}", time.minute),
+            Specifier::Second => write!(writer, "{:02}", time.second),
+            Specifier::Fraction(digits) => write!(
+                writer,
+                "{:0width$}",
+                self.nanosecond.unwrap_or(0) / 10u32.pow(9 - u32::from(digits)),
+                width = usize::from(digits)
+            ),
+            Specifier::IsoTime => write!(writer, "{}", time),
+            Specifier::ShortTime => write!(writer, "{:02}:{:02}", time.hour, time.minute),
+            Specifier::Text(text) => writer.write_str(text),
+        }
+    }
+}
+
+impl Date {
+    /// Writes date formatted with strftime-like spec, time specifiers are rejected
+    /// with [FormatError::MissingField]
+    pub fn strftime<W: fmt::Write>(&self, writer: &mut W, spec: &str) -> Result<(), FormatError> {
+        let fields = Fields {
+            date: Some(*self),
+            time: None,
+            nanosecond: None,
+        };
+        fields.write(writer, spec)
+    }
+}
+
+impl Time {
+    /// Writes time formatted with strftime-like spec, date and fraction of second specifiers
+    /// are rejected with [FormatError::MissingField]
+    /// ```
+    /// use stm32f3_rtc::datetime::Time;
+    /// use stm32f3_rtc::strftime::FormatError;
+    /// let mut text = String::new();
+    /// Time::from(19, 5, 0).strftime(&mut text, "%l:%M %p").unwrap();
+    /// assert_eq!(text, " 7:05 PM");
+    /// assert_eq!(
+    ///     Time::from(19, 5, 0).strftime(&mut text, "%F"),
+    ///     Err(FormatError::MissingField(0))
+    /// );
+    /// ```
+    pub fn strftime<W: fmt::Write>(&self, writer: &mut W, spec: &str) -> Result<(), FormatError> {
+        let fields = Fields {
+            date: None,
+            time: Some(*self),
+            nanosecond: None,
+        };
+        fields.write(writer, spec)
+    }
+}
+
+impl DateTime {
+    /// Writes date and time formatted with strftime-like spec
+    /// ```
+    /// use stm32f3_rtc::datetime::{Date, DateTime, Time};
+    /// let date_time = DateTime::from(Date::from(1, 6, 2024), Time::from(12, 30, 0), 250_000_000);
+    /// let mut text = String::new();
+    /// date_time.strftime(&mut text, "%A %j %T.%3f").unwrap();
+    /// assert_eq!(text, "Saturday 153 12:30:00.250");
+    /// ```
+    pub fn strftime<W: fmt::Write>(&self, writer: &mut W, spec: &str) -> Result<(), FormatError> {
+        let fields = Fields {
+            date: Some(self.date),
+            time: Some(self.time),
+            nanosecond: Some(self.nanosecond),
+        };
+        fields.write(writer, spec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate, FormatError};
+    use datetime::{Date, DateTime, Time};
+    use iso8601::SliceWriter;
+
+    #[test]
+    fn invalid_specs_are_rejected() {
+        #[rustfmt::skip]
+        let cases = [
+            ("%Q", FormatError::UnsupportedSpecifier(0)),
+            ("%d.%m.%Q", FormatError::UnsupportedSpecifier(6)),
+            ("%0f", FormatError::UnsupportedSpecifier(0)),
+            ("%3", FormatError::UnsupportedSpecifier(0)),
+            ("%3g", FormatError::UnsupportedSpecifier(0)),
+            ("100%", FormatError::Incomplete),
+            ("%", FormatError::Incomplete),
+        ];
+        for &(spec, error) in cases.iter() {
+            assert_eq!(validate(spec), Err(error), "{:?}", spec);
+            let mut buffer = [0u8; 16];
+            let mut writer = SliceWriter::new(&mut buffer);
+            let date_time = DateTime::from(Date::from(1, 6, 2024), Time::from(7, 5, 9), 0);
+            assert_eq!(
+                date_time.strftime(&mut writer, spec),
+                Err(error),
+                "{:?}",
+                spec
+            );
+            assert!(writer.is_empty(), "{:?}", spec);
+        }
+    }
+
+    #[test]
+    fn missing_fields_are_rejected_before_writing() {
+        let mut buffer = [0u8; 16];
+        let mut writer = SliceWriter::new(&mut buffer);
+        assert_eq!(
+            Date::from(1, 6, 2024).strftime(&mut writer, "%F %H"),
+            Err(FormatError::MissingField(3))
+        );
+        assert_eq!(
+            Time::from(7, 5, 9).strftime(&mut writer, "%T %3f"),
+            Err(FormatError::MissingField(3))
+        );
+        assert_eq!(
+            Time::from(7, 5, 9).strftime(&mut writer, "%%%d"),
+            Err(FormatError::MissingField(2))
+        );
+        assert!(writer.is_empty());
+    }
+
+    #[test]
+    fn full_buffer_is_write_error() {
+        let date_time = DateTime::from(Date::from(1, 6, 2024), Time::from(19, 5, 9), 0);
+        let mut buffer = [0u8; 8];
+        let mut writer = SliceWriter::new(&mut buffer);
+        assert_eq!(
+            date_time.strftime(&mut writer, "%A %B"),
+            Err(FormatError::Write)
+        );
+        let mut writer = SliceWriter::new(&mut buffer);
+        date_time.strftime(&mut writer, "%I%p%n%%").unwrap();
+        assert_eq!(writer.as_str(), "07PM\n%");
+    }
+}
@@ -0,0 +1,120 @@
+//! Calendar based tick counter shared by the scheduler integrations (embassy, RTIC).
+//!
+//! Tick is one step of synchronous prescaler, so there are **PREDIV_S + 1** ticks
+//! in a second. Counter starts at 1 January 2000 00:00:00.
+use century;
+use datetime::{Bcd, Date};
+use rtc::{read_calendar, unprotected};
+use stm32f3xx_hal::pac::rtc::RegisterBlock;
+use stm32f3xx_hal::pac::{Interrupt, EXTI, NVIC};
+
+/// Date of tick 0
+const EPOCH: Date = Date::from(1, 1, 2000);
+
+/// Returns amount of ticks in one second (PREDIV_S + 1)
+pub(crate) fn tick_rate(rtc: &RegisterBlock) -> u64 {
+    u64::from(rtc.prer.read().prediv_s().bits()) + 1
+}
+
+/// Reads calendar and sub seconds as a tick counter
+pub(crate) fn ticks(rtc: &RegisterBlock) -> u64 {
+    let (date, time, ssr) = read_calendar(rtc);
+    let prediv_s = tick_rate(rtc) - 1;
+    // Calendar can start before 2000 with earlier base year, counter stays at 0 until epoch
+    let seconds = date.days_since(&EPOCH).max(0) as u64 * 86_400 + u64::from(time.to_seconds());
+    // SSR is counting down from PREDIV_S, value above it comes from shift operation
+    seconds * (prediv_s + 1) + prediv_s.saturating_sub(u64::from(ssr))
+}
+
+/// Enables RTC alarm interrupt through EXTI line 17 and makes sure that Alarm A is off
+pub(crate) fn enable_alarm_interrupt(rtc: &RegisterBlock, exti: &mut EXTI) {
+    disable_alarm(rtc);
+    exti.imr1.modify(|_, w| w.mr17().unmasked());
+    exti.rtsr1.modify(|_, w| w.tr17().enabled());
+    unsafe { NVIC::unmask(Interrupt::RTCALARM) };
+}
+
+/// Sets Alarm A to a given tick
+pub(crate) fn set_alarm(rtc: &RegisterBlock, at: u64) {
+    let prediv_s = tick_rate(rtc) - 1;
+    let seconds = at / (prediv_s + 1);
+    let sub_second = at % (prediv_s + 1);
+    let date = match Date::from_days_since(&EPOCH, (seconds / 86_400) as i32) {
+        Some(date) if date.year <= century::year_range().1 => date,
+        // Calendar ends with the last supported year, later moment is never going to come
+        _ => {
+            disable_alarm(rtc);
+            return;
+        }
+    };
+    let second_of_day = (seconds % 86_400) as u32;
+    let mut hour = (second_of_day / 3600) as u8;
+    let pm = rtc.cr.read().fmt().bit_is_set() && hour >= 12;
+    if rtc.cr.read().fmt().bit_is_set() {
+        hour = match hour % 12 {
+            0 => 12,
+            h => h,
+        };
+    }
+    let day = Bcd::set(date.day);
+    let hour = Bcd::set(hour);
+    let minute = Bcd::set((second_of_day % 3600 / 60) as u8);
+    let second = Bcd::set((second_of_day % 60) as u8);
+
+    unprotected(rtc, |rtc| {
+        rtc.cr.modify(|_, w| w.alrae().clear_bit());
+        while rtc.isr.read().alrawf().bit_is_clear() {}
+
+        // Alarm compares day of month, so alarm that is more than a month away
+        // can fire earlier, then it has to be just set again from interrupt
+        rtc.alrmr[0].write(|w| {
+            w.msk4().clear_bit();
+            w.wdsel().clear_bit();
+            w.dt().bits(day.tens);
+            w.du().bits(day.units);
+            w.msk3().clear_bit();
+            w.pm().bit(pm);
+            w.ht().bits(hour.tens);
+            w.hu().bits(hour.units);
+            w.msk2().clear_bit();
+            w.mnt().bits(minute.tens);
+            w.mnu().bits(minute.units);
+            w.msk1().clear_bit();
+            w.st().bits(second.tens);
+            w.su().bits(second.units)
+        });
+        rtc.alrmssr[0].write(|w| {
+            w.maskss().bits(15);
+            w.ss().bits((prediv_s - sub_second) as u16)
+        });
+        rtc.isr.modify(|_, w| w.alraf().clear_bit());
+        rtc.cr.modify(|_, w| {
+            w.alraie().set_bit();
+            w.alrae().set_bit()
+        });
+    });
+}
+
+/// Disables Alarm A
+pub(crate) fn disable_alarm(rtc: &RegisterBlock) {
+    unprotected(rtc, |rtc| {
+        rtc.cr.modify(|_, w| {
+            w.alrae().clear_bit();
+            w.alraie().clear_bit()
+        });
+        rtc.isr.modify(|_, w| w.alraf().clear_bit())
+    });
+}
+
+/// Clears Alarm A flag together with its EXTI line and synchronizes shadow registers,
+/// that has to be done after wake up from low power mode
+pub(crate) fn acknowledge_alarm(rtc: &RegisterBlock) {
+    unprotected(rtc, |rtc| {
+        rtc.isr.modify(|_, w| {
+            w.alraf().clear_bit();
+            w.rsf().clear_bit()
+        })
+    });
+    unsafe { (*EXTI::PTR).pr1.write(|w| w.pr17().set_bit()) };
+    while rtc.isr.read().rsf().bit_is_clear() {}
+}
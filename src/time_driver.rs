@@ -0,0 +1,128 @@
+use core::cell::RefCell;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::task::Waker;
+use critical_section::{CriticalSection, Mutex};
+use embassy_time_driver::{Driver, TICK_HZ};
+use embassy_time_queue_utils::Queue;
+use rtc::Rtc;
+use stm32f3xx_hal::interrupt;
+use stm32f3xx_hal::pac::rtc::RegisterBlock;
+use stm32f3xx_hal::pac::{EXTI, RTC};
+use tick_counter;
+
+embassy_time_driver::time_driver_impl!(static DRIVER: RtcDriver = RtcDriver::new());
+
+/// Time driver for [embassy-time](https://docs.rs/embassy-time) that runs from the RTC, so
+/// the system can stay in **STOP** mode between tasks.
+///
+/// Time is counted as 64-bit tick counter built from calendar (days since 1 January 2000,
+/// hours, minutes, seconds) and sub second register (SSR). Wakes are scheduled with **Alarm A**
+/// and its sub second comparison.
+///
+/// ## Tick rate
+/// One tick is one step of synchronous prescaler, so tick rate is equal to **PREDIV_S + 1**
+/// and it has to be the same as one selected by `tick-hz-*` feature of `embassy-time`:
+/// - LSE with default prescalers (a: 127, s: 255) - `tick-hz-256`
+/// - LSI with prescalers a: 99, s: 399 - `tick-hz-400`
+///
+/// [init] panics if tick rate do not match.
+///
+/// **Note:** Changing time or date with **TimeAccess** or **DateAccess** moves the tick counter,
+/// embassy expects time that never goes back, so please set calendar before [init] is called.
+///
+/// ## Usage:
+/// ```
+/// use stm32f3_rtc::rtc::{ClockSource, Rtc};
+/// use stm32f3_rtc::time_driver;
+/// use stm32f3xx_hal::pac;
+///
+/// let mut peripheral = pac::Peripherals::take().unwrap();
+/// let mut rtc = Rtc::new(peripheral.RTC);
+/// rtc.set_clock_source(ClockSource::LSE(true));
+/// rtc.start_clock(&mut peripheral.PWR, &mut peripheral.RCC);
+/// time_driver::init(&mut rtc, &mut peripheral.EXTI);
+/// ```
+pub struct RtcDriver {
+    initialized: AtomicBool,
+    queue: Mutex<RefCell<Queue>>,
+}
+
+/// Enables RTC time driver. It has to be called after RTC clock is started.
+///
+/// It disables Alarm A for any other usage and enables **RTCALARM** interrupt
+/// through EXTI line 17.
+pub fn init(rtc: &mut Rtc, exti: &mut EXTI) {
+    assert!(
+        TICK_HZ == tick_counter::tick_rate(&rtc.rtc),
+        "embassy tick rate has to be equal to PREDIV_S + 1"
+    );
+    tick_counter::enable_alarm_interrupt(&rtc.rtc, exti);
+    DRIVER.initialized.store(true, Ordering::SeqCst);
+}
+
+impl RtcDriver {
+    const fn new() -> Self {
+        Self {
+            initialized: AtomicBool::new(false),
+            queue: Mutex::new(RefCell::new(Queue::new())),
+        }
+    }
+
+    fn registers(&self) -> &'static RegisterBlock {
+        unsafe { &*RTC::PTR }
+    }
+
+    /// Sets Alarm A to a given tick. Returns false if that moment already passed.
+    fn set_alarm(&self, _cs: CriticalSection, at: u64) -> bool {
+        let rtc = self.registers();
+        if at == u64::MAX {
+            tick_counter::disable_alarm(rtc);
+            return true;
+        }
+        tick_counter::set_alarm(rtc, at);
+        // Alarm set too close to current time might be missed
+        if tick_counter::ticks(rtc) >= at {
+            tick_counter::disable_alarm(rtc);
+            return false;
+        }
+        true
+    }
+
+    fn on_alarm(&self) {
+        let rtc = self.registers();
+        tick_counter::acknowledge_alarm(rtc);
+        critical_section::with(|cs| {
+            let mut queue = self.queue.borrow(cs).borrow_mut();
+            let mut next = queue.next_expiration(tick_counter::ticks(rtc));
+            while !self.set_alarm(cs, next) {
+                next = queue.next_expiration(tick_counter::ticks(rtc));
+            }
+        });
+    }
+}
+
+impl Driver for RtcDriver {
+    fn now(&self) -> u64 {
+        if !self.initialized.load(Ordering::Relaxed) {
+            return 0;
+        }
+        tick_counter::ticks(self.registers())
+    }
+
+    fn schedule_wake(&self, at: u64, waker: &Waker) {
+        critical_section::with(|cs| {
+            let mut queue = self.queue.borrow(cs).borrow_mut();
+            if queue.schedule_wake(at, waker) {
+                let mut next = queue.next_expiration(self.now());
+                while !self.set_alarm(cs, next) {
+                    next = queue.next_expiration(self.now());
+                }
+            }
+        })
+    }
+}
+
+#[interrupt]
+fn RTCALARM() {
+    DRIVER.on_alarm();
+}
@@ -0,0 +1,113 @@
+//! Conversions between [datetime](crate::datetime) types and [time](https://docs.rs/time)
+//! crate **Date**, **Time** and **PrimitiveDateTime**, together with [Rtc] methods that
+//! set and read calendar directly as time crate types.
+//!
+//! ## Usage:
+//! ```
+//! use stm32f3_rtc::rtc::Rtc;
+//! use stm32f3xx_hal::pac;
+//! use time::macros::datetime;
+//!
+//! let mut peripheral = pac::Peripherals::take().unwrap();
+//! let mut rtc = Rtc::new(peripheral.RTC);
+//! rtc.start_clock(&mut peripheral.PWR, &mut peripheral.RCC);
+//! rtc.set_primitive_datetime(&datetime!(2024-06-01 12:30:00.5)).unwrap();
+//! let now = rtc.primitive_datetime().unwrap();
+//! ```
+use core::convert::TryFrom;
+use datetime::{Date, DateTime, Time};
+use rtc::Rtc;
+use time;
+
+/// Errors returned by time crate conversions, shared with other conversions of the crate
+pub use datetime::ConversionError as Error;
+
+impl TryFrom<Date> for time::Date {
+    type Error = Error;
+
+    fn try_from(date: Date) -> Result<Self, Self::Error> {
+        let year = i32::try_from(date.year).map_err(|_| Error::InvalidInputData)?;
+        let month = time::Month::try_from(date.month).map_err(|_| Error::InvalidInputData)?;
+        time::Date::from_calendar_date(year, month, date.day).map_err(|_| Error::InvalidInputData)
+    }
+}
+
+impl TryFrom<time::Date> for Date {
+    type Error = Error;
+
+    fn try_from(date: time::Date) -> Result<Self, Self::Error> {
+        let year = u32::try_from(date.year()).map_err(|_| Error::InvalidInputData)?;
+        Ok(Date::from(date.day(), u8::from(date.month()), year))
+    }
+}
+
+impl TryFrom<Time> for time::Time {
+    type Error = Error;
+
+    fn try_from(time: Time) -> Result<Self, Self::Error> {
+        time::Time::from_hms(time.hour, time.minute, time.second)
+            .map_err(|_| Error::InvalidInputData)
+    }
+}
+
+impl TryFrom<time::Time> for Time {
+    type Error = Error;
+
+    /// Create Time from time crate Time, returns error when it has fraction of second.
+    /// Please use **DateTime** conversion to keep it.
+    fn try_from(time: time::Time) -> Result<Self, Self::Error> {
+        match time.nanosecond() {
+            0 => Ok(whole_seconds(&time)),
+            _ => Err(Error::InvalidInputData),
+        }
+    }
+}
+
+/// Returns time without fraction of second, that is kept separately by **DateTime**
+fn whole_seconds(time: &time::Time) -> Time {
+    Time::from(time.hour(), time.minute(), time.second())
+}
+
+impl TryFrom<DateTime> for time::PrimitiveDateTime {
+    type Error = Error;
+
+    fn try_from(date_time: DateTime) -> Result<Self, Self::Error> {
+        let time = time::Time::try_from(date_time.time)?
+            .replace_nanosecond(date_time.nanosecond)
+            .map_err(|_| Error::InvalidInputData)?;
+        Ok(time::PrimitiveDateTime::new(
+            time::Date::try_from(date_time.date)?,
+            time,
+        ))
+    }
+}
+
+impl TryFrom<time::PrimitiveDateTime> for DateTime {
+    type Error = Error;
+
+    fn try_from(date_time: time::PrimitiveDateTime) -> Result<Self, Self::Error> {
+        Ok(DateTime::from(
+            Date::try_from(date_time.date())?,
+            whole_seconds(&date_time.time()),
+            date_time.nanosecond(),
+        ))
+    }
+}
+
+impl Rtc {
+    /// Returns current date and time as time crate PrimitiveDateTime, fraction of second
+    /// is kept as nanoseconds
+    pub fn primitive_datetime(&self) -> Result<time::PrimitiveDateTime, Error> {
+        time::PrimitiveDateTime::try_from(self.date_time())
+    }
+
+    /// Set date and time from time crate PrimitiveDateTime, including fraction of second
+    ///
+    /// **Note:** Returns error if year is out of range given by **Rtc::year_range()**
+    pub fn set_primitive_datetime(
+        &mut self,
+        date_time: &time::PrimitiveDateTime,
+    ) -> Result<(), Error> {
+        self.set_date_time(DateTime::try_from(*date_time)?)
+    }
+}
@@ -0,0 +1,47 @@
+//! Time source for [embedded-sdmmc](https://docs.rs/embedded-sdmmc), so files written on
+//! SD cards get real creation and modification dates instead of 1980-01-01.
+//!
+//! ## Usage:
+//! ```
+//! use embedded_sdmmc::VolumeManager;
+//! use stm32f3_rtc::rtc::Rtc;
+//! use stm32f3xx_hal::pac;
+//!
+//! let mut peripheral = pac::Peripherals::take().unwrap();
+//! let mut rtc = Rtc::new(peripheral.RTC);
+//! rtc.start_clock(&mut peripheral.PWR, &mut peripheral.RCC);
+//! let volume_manager = VolumeManager::new(sd_card, rtc);
+//! ```
+use datetime::{Date, Time};
+use embedded_sdmmc::{TimeSource, Timestamp};
+use rtc::{read_calendar, Rtc};
+
+/// First year that FAT timestamp can keep
+const FAT_FIRST_YEAR: u32 = 1980;
+/// Last year that FAT timestamp can keep
+const FAT_LAST_YEAR: u32 = 2107;
+
+impl TimeSource for Rtc {
+    /// Returns current date and time as embedded-sdmmc Timestamp, dates out of FAT range
+    /// (1980 - 2107) are clamped to its first or last second
+    fn get_timestamp(&self) -> Timestamp {
+        let (date, time, _) = read_calendar(&self.rtc);
+        let (date, time) = match date.year {
+            year if year < FAT_FIRST_YEAR => {
+                (Date::from(1, 1, FAT_FIRST_YEAR), Time::from(0, 0, 0))
+            }
+            year if year > FAT_LAST_YEAR => {
+                (Date::from(31, 12, FAT_LAST_YEAR), Time::from(23, 59, 59))
+            }
+            _ => (date, time),
+        };
+        Timestamp {
+            year_since_1970: (date.year - 1970) as u8,
+            zero_indexed_month: date.month - 1,
+            zero_indexed_day: date.day - 1,
+            hours: time.hour,
+            minutes: time.minute,
+            seconds: time.second,
+        }
+    }
+}
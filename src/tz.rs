@@ -0,0 +1,416 @@
+//! [POSIX TZ](https://pubs.opengroup.org/onlinepubs/9699919799/basedefs/V1_chap08.html)
+//! strings parser and conversion from UTC into local time, without allocation.
+//!
+//! It lets RTC run in UTC, while local time is calculated only for display:
+//! - `CET-1CEST,M3.5.0,M10.5.0/3` - Central European Time
+//! - `EST5EDT,M3.2.0,M11.1.0` - US Eastern Time
+//! - `<+0530>-5:30` - zone without daylight saving and with quoted name
+//!
+//! **Note:** POSIX offsets are written west of UTC, so `CET-1` means UTC+1. Parsed
+//! [TimeZone] keeps offsets as seconds east of UTC.
+//!
+//! ## Usage:
+//! ```
+//! use stm32f3_rtc::datetime::{Date, DateTime, Time};
+//! use stm32f3_rtc::tz::TimeZone;
+//!
+//! const ZONE: &str = "CET-1CEST,M3.5.0,M10.5.0/3";
+//! let zone = TimeZone::parse(ZONE).unwrap();
+//!
+//! let utc = DateTime::from(Date::from(1, 6, 2024), Time::from(22, 30, 0), 0);
+//! let local = zone.to_local(&utc);
+//! assert_eq!(local.date_time.date, Date::from(2, 6, 2024));
+//! assert_eq!(local.date_time.time, Time::from(0, 30, 0));
+//! assert_eq!((local.name, local.offset, local.is_dst), ("CEST", 7200, true));
+//! ```
+use datetime::{Date, DateTime, Time, Weekday};
+use dst::{DstRule, Week};
+use rtc::Rtc;
+
+/// Errors returned by TZ string parsing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    /// Zone name is missing, shorter than 3 characters or quoted name is not closed with `>`
+    InvalidName,
+    /// UTC offset or transition time is missing or out of range
+    InvalidOffset,
+    /// Transition rule is missing or out of range
+    InvalidRule,
+    /// Text has characters after the last field
+    TrailingCharacters,
+}
+
+/// Day of the year on which transition happens
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RuleDate {
+    /// `Jn` - day of the year 1 - 365, 29 February is never counted
+    Julian(u16),
+    /// `n` - day of the year 0 - 365, 29 February is counted in leap years
+    ZeroBased(u16),
+    /// `Mm.w.d` - weekday of given week of the month, week 5 means the last one
+    MonthWeekDay {
+        month: u8,
+        week: Week,
+        weekday: Weekday,
+    },
+}
+
+impl RuleDate {
+    /// Returns day of the year counted from 0 in given year
+    fn zero_based_day(&self, year: u32) -> i32 {
+        match *self {
+            RuleDate::Julian(day) => {
+                i32::from(day) - 1 + i32::from(Date::is_leap_year(year) && day >= 60)
+            }
+            RuleDate::ZeroBased(day) => i32::from(day),
+            RuleDate::MonthWeekDay {
+                month,
+                week,
+                weekday,
+            } => {
+                let date = DstRule::from(week, weekday, month, Time::from(0, 0, 0)).date(year);
+                i32::from(date.day_of_year()) - 1
+            }
+        }
+    }
+}
+
+/// Single daylight saving transition, e.g. `M3.5.0/2`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ZoneTransition {
+    pub date: RuleDate,
+    /// Local time of transition in seconds, it can be negative or bigger than one day
+    pub time: i32,
+}
+
+/// Daylight saving part of time zone
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DaylightSaving<'a> {
+    pub name: &'a str,
+    /// Offset east of UTC in seconds
+    pub offset: i32,
+    /// Start of daylight saving, given in local standard time
+    pub start: ZoneTransition,
+    /// End of daylight saving, given in local daylight saving time
+    pub end: ZoneTransition,
+}
+
+/// Time zone read from POSIX TZ string, names are borrowed from parsed text
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimeZone<'a> {
+    pub name: &'a str,
+    /// Standard time offset east of UTC in seconds
+    pub offset: i32,
+    pub daylight_saving: Option<DaylightSaving<'a>>,
+}
+
+/// Local date and time together with zone details used to calculate it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LocalDateTime<'a> {
+    pub date_time: DateTime,
+    /// Offset east of UTC in seconds
+    pub offset: i32,
+    /// Zone name, e.g. `CET` or `CEST`
+    pub name: &'a str,
+    /// True when daylight saving time is in use
+    pub is_dst: bool,
+}
+
+/// Reads TZ string fields one after another
+struct Parser<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.position).cloned()
+    }
+
+    /// Skips given character if it is next one
+    fn skip(&mut self, expected: u8) -> bool {
+        let found = self.peek() == Some(expected);
+        self.position += usize::from(found);
+        found
+    }
+
+    /// Reads characters while they match the condition
+    fn take_while<F: Fn(u8) -> bool>(&mut self, condition: F) -> &'a str {
+        let start = self.position;
+        while self.peek().is_some_and(&condition) {
+            self.position += 1;
+        }
+        &self.text[start..self.position]
+    }
+
+    /// Reads number with up to `digits` digits, that is not bigger than `max`
+    fn number(&mut self, digits: usize, max: u32) -> Option<u32> {
+        let text = self.take_while(|byte| byte.is_ascii_digit());
+        if text.is_empty() || text.len() > digits {
+            return None;
+        }
+        text.parse().ok().filter(|&value| value <= max)
+    }
+
+    /// Reads `name` or `<name>`
+    fn name(&mut self) -> Result<&'a str, ParseError> {
+        let name = match self.skip(b'<') {
+            true => {
+                let name = self.take_while(|byte| {
+                    byte.is_ascii_alphanumeric() || byte == b'+' || byte == b'-'
+                });
+                if !self.skip(b'>') {
+                    return Err(ParseError::InvalidName);
+                }
+                name
+            }
+            false => self.take_while(|byte| byte.is_ascii_alphabetic()),
+        };
+        match name.len() >= 3 {
+            true => Ok(name),
+            false => Err(ParseError::InvalidName),
+        }
+    }
+
+    /// Reads `[+|-]hh[:mm[:ss]]` and returns it in seconds
+    fn duration(&mut self, max_hours: u32) -> Result<i32, ParseError> {
+        let negative = self.skip(b'-');
+        if !negative {
+            self.skip(b'+');
+        }
+        let hours = self.number(3, max_hours).ok_or(ParseError::InvalidOffset)?;
+        let mut seconds = hours * 3600;
+        if self.skip(b':') {
+            seconds += self.number(2, 59).ok_or(ParseError::InvalidOffset)? * 60;
+            if self.skip(b':') {
+                seconds += self.number(2, 59).ok_or(ParseError::InvalidOffset)?;
+            }
+        }
+        match negative {
+            true => Ok(-(seconds as i32)),
+            false => Ok(seconds as i32),
+        }
+    }
+
+    /// Reads POSIX offset (west of UTC) and returns it east of UTC
+    fn offset(&mut self) -> Result<i32, ParseError> {
+        Ok(-self.duration(24)?)
+    }
+
+    /// Reads `,rule[/time]`
+    fn transition(&mut self) -> Result<ZoneTransition, ParseError> {
+        if !self.skip(b',') {
+            return Err(ParseError::InvalidRule);
+        }
+        let date = match self.peek() {
+            Some(b'J') => {
+                self.position += 1;
+                match self.number(3, 365) {
+                    Some(day) if day > 0 => RuleDate::Julian(day as u16),
+                    _ => return Err(ParseError::InvalidRule),
+                }
+            }
+            Some(b'M') => {
+                self.position += 1;
+                let month = self.number(2, 12).filter(|&month| month > 0);
+                let week = match self.skip(b'.') {
+                    true => self.number(1, 5),
+                    false => None,
+                };
+                let weekday = match self.skip(b'.') {
+                    true => self.number(1, 6),
+                    false => None,
+                };
+                let week = match week {
+                    Some(1) => Week::First,
+                    Some(2) => Week::Second,
+                    Some(3) => Week::Third,
+                    Some(4) => Week::Fourth,
+                    Some(5) => Week::Last,
+                    _ => return Err(ParseError::InvalidRule),
+                };
+                match (month, weekday) {
+                    (Some(month), Some(weekday)) => RuleDate::MonthWeekDay {
+                        month: month as u8,
+                        week,
+                        weekday: Weekday::from_days_from_sunday(weekday as u8),
+                    },
+                    _ => return Err(ParseError::InvalidRule),
+                }
+            }
+            _ => RuleDate::ZeroBased(self.number(3, 365).ok_or(ParseError::InvalidRule)? as u16),
+        };
+        // Transition time defaults to 02:00:00, RFC 8536 extends its range to ±167 hours
+        let time = match self.skip(b'/') {
+            true => self.duration(167)?,
+            false => 7200,
+        };
+        Ok(ZoneTransition { date, time })
+    }
+}
+
+impl TimeZone<'static> {
+    /// Coordinated Universal Time, zone used by [Rtc] until other one is set
+    pub const UTC: TimeZone<'static> = TimeZone {
+        name: "UTC",
+        offset: 0,
+        daylight_saving: None,
+    };
+}
+
+impl<'a> TimeZone<'a> {
+    /// Parses POSIX TZ string. Daylight saving zones need transition rules
+    /// (e.g. `,M3.5.0,M10.5.0/3`), as POSIX does not define default ones.
+    /// ```
+    /// use stm32f3_rtc::tz::{ParseError, TimeZone};
+    /// let zone = TimeZone::parse("EST5EDT,M3.2.0,M11.1.0").unwrap();
+    /// assert_eq!((zone.name, zone.offset), ("EST", -5 * 3600));
+    /// assert_eq!(zone.daylight_saving.unwrap().offset, -4 * 3600);
+    /// assert_eq!(TimeZone::parse("<+0530>-5:30").unwrap().offset, 19_800);
+    /// assert_eq!(TimeZone::parse("CET-1CEST"), Err(ParseError::InvalidRule));
+    /// assert_eq!(TimeZone::parse("UT0"), Err(ParseError::InvalidName));
+    /// ```
+    pub fn parse(text: &'a str) -> Result<TimeZone<'a>, ParseError> {
+        let mut parser = Parser { text, position: 0 };
+        let name = parser.name()?;
+        let offset = parser.offset()?;
+        let daylight_saving = match parser.peek() {
+            None => None,
+            Some(_) => {
+                let dst_name = parser.name()?;
+                let dst_offset = match parser.peek() {
+                    Some(b',') | None => offset + 3600,
+                    Some(_) => parser.offset()?,
+                };
+                Some(DaylightSaving {
+                    name: dst_name,
+                    offset: dst_offset,
+                    start: parser.transition()?,
+                    end: parser.transition()?,
+                })
+            }
+        };
+        match parser.position == text.len() {
+            true => Ok(TimeZone {
+                name,
+                offset,
+                daylight_saving,
+            }),
+            false => Err(ParseError::TrailingCharacters),
+        }
+    }
+
+    /// Returns true if daylight saving time is in use at given UTC date and time
+    pub fn is_dst(&self, utc: &DateTime) -> bool {
+        let dst = match self.daylight_saving {
+            Some(ref dst) => dst,
+            None => return false,
+        };
+        let year = utc.date.year;
+        let now = (i32::from(utc.date.day_of_year()) - 1) * 86_400 + utc.time.to_seconds() as i32;
+        // Both transitions are moved into UTC
+        let start = dst.start.date.zero_based_day(year) * 86_400 + dst.start.time - self.offset;
+        let end = dst.end.date.zero_based_day(year) * 86_400 + dst.end.time - dst.offset;
+        match start < end {
+            true => now >= start && now < end,
+            // Southern hemisphere, daylight saving lasts over new year
+            false => now >= start || now < end,
+        }
+    }
+
+    /// Converts UTC date and time into local date and time
+    pub fn to_local(&self, utc: &DateTime) -> LocalDateTime<'a> {
+        let (offset, name, is_dst) = match self.daylight_saving {
+            Some(dst) if self.is_dst(utc) => (dst.offset, dst.name, true),
+            _ => (self.offset, self.name, false),
+        };
+        LocalDateTime {
+            date_time: utc.add_seconds(offset),
+            offset,
+            name,
+            is_dst,
+        }
+    }
+}
+
+impl Rtc {
+    /// Keeps time zone used by **local_date_time()**, RTC has to keep UTC. Zone is parsed from
+    /// static text, e.g. constant or text kept in flash.
+    /// ```
+    /// const ZONE: &str = "CET-1CEST,M3.5.0,M10.5.0/3";
+    /// rtc.set_time_zone(TimeZone::parse(ZONE).unwrap());
+    /// ```
+    pub fn set_time_zone(&mut self, zone: TimeZone<'static>) -> &Self {
+        self.time_zone = zone;
+        self
+    }
+
+    /// Returns time zone kept by **set_time_zone()**, [TimeZone::UTC] by default
+    pub fn time_zone(&self) -> &TimeZone<'static> {
+        &self.time_zone
+    }
+
+    /// Returns local date and time in the zone kept by **set_time_zone()**
+    /// ```
+    /// let local = rtc.local_date_time();
+    /// hprintln!("{} {}", local.date_time, local.name);
+    /// ```
+    pub fn local_date_time(&self) -> LocalDateTime<'static> {
+        self.time_zone.to_local(&self.date_time())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ParseError, RuleDate, TimeZone};
+
+    #[test]
+    fn malformed_strings_are_rejected() {
+        #[rustfmt::skip]
+        let cases = [
+            ("", ParseError::InvalidName),
+            ("UT0", ParseError::InvalidName),
+            ("<+0530-5:30", ParseError::InvalidName),
+            ("<+0>0", ParseError::InvalidName),
+            ("CET-1C", ParseError::InvalidName),
+            ("CET", ParseError::InvalidOffset),
+            ("CET-", ParseError::InvalidOffset),
+            ("CET-25", ParseError::InvalidOffset),
+            ("CET-1:60", ParseError::InvalidOffset),
+            ("CET-1:00:60", ParseError::InvalidOffset),
+            ("CET-1CEST,M3.5.0/", ParseError::InvalidOffset),
+            ("CET-1CEST,M3.5.0/168,M10.5.0", ParseError::InvalidOffset),
+            ("CET-1CEST", ParseError::InvalidRule),
+            ("CET-1CEST,M3.5.0", ParseError::InvalidRule),
+            ("CET-1CEST,M13.5.0,M10.5.0", ParseError::InvalidRule),
+            ("CET-1CEST,M0.5.0,M10.5.0", ParseError::InvalidRule),
+            ("CET-1CEST,M3.6.0,M10.5.0", ParseError::InvalidRule),
+            ("CET-1CEST,M3.5.7,M10.5.0", ParseError::InvalidRule),
+            ("CET-1CEST,M3.5,M10.5.0", ParseError::InvalidRule),
+            ("CET-1CEST,J0,M10.5.0", ParseError::InvalidRule),
+            ("CET-1CEST,366,M10.5.0", ParseError::InvalidRule),
+            ("CET-1CEST,X,M10.5.0", ParseError::InvalidRule),
+            ("CET-1CEST,M3.5.0,M10.5.0 ", ParseError::TrailingCharacters),
+            ("CET-1CEST,M3.5.0,M10.5.0/3,", ParseError::TrailingCharacters),
+        ];
+        for &(text, error) in cases.iter() {
+            assert_eq!(TimeZone::parse(text), Err(error), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn rule_dates_are_parsed() {
+        let zone = TimeZone::parse("<-03>3<-02>,J60/-1,365/25:30:15").unwrap();
+        assert_eq!((zone.name, zone.offset), ("-03", -3 * 3600));
+        let dst = zone.daylight_saving.unwrap();
+        assert_eq!((dst.name, dst.offset), ("-02", -2 * 3600));
+        assert_eq!(
+            (dst.start.date, dst.start.time),
+            (RuleDate::Julian(60), -3600)
+        );
+        assert_eq!(
+            (dst.end.date, dst.end.time),
+            (RuleDate::ZeroBased(365), 91_815)
+        );
+    }
+}
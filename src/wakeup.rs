@@ -1,11 +1,100 @@
-use rtc::{Protection, Rtc};
+use core::cell::{Cell, RefCell};
+use core::mem;
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use core::time::Duration;
+use cortex_m::interrupt::{free, Mutex};
+use datetime::Date;
+use rtc::{read_date_time, unprotected, Rtc};
 use rtc_interrupt::RtcInterrupt;
+#[cfg(not(feature = "external-wakeup-interrupt"))]
 use stm32f3xx_hal::interrupt;
+use stm32f3xx_hal::pac::rtc::RegisterBlock;
 use stm32f3xx_hal::pac::{Interrupt, EXTI, NVIC, RTC};
 
-static mut INSTANCE: Option<fn()> = None;
+/// Nanoseconds in one second
+const NANOS: u128 = 1_000_000_000;
+
+/// Maximum amount of handlers, that can be registered at once
+pub const MAX_HANDLERS: usize = 4;
+
+/// Handler called from wake up interrupt
+#[derive(Clone, Copy)]
+enum Handler {
+    Function(fn()),
+    /// Type erased `fn(&mut T)` with its context, called through `call_with_context::<T>`
+    Context {
+        call: fn(*const (), *mut ()),
+        function: *const (),
+        context: *mut (),
+    },
+}
+
+// Context is taken as exclusive 'static reference to Send type and function pointer is Send
+unsafe impl Send for Handler {}
+
+/// Restores types of context handler and calls it
+fn call_with_context<T>(function: *const (), context: *mut ()) {
+    let function = unsafe { mem::transmute::<*const (), fn(&mut T)>(function) };
+    function(unsafe { &mut *(context as *mut T) })
+}
+
+const NO_HANDLER: Option<Handler> = None;
+
+static HANDLERS: Mutex<RefCell<[Option<Handler>; MAX_HANDLERS]>> =
+    Mutex::new(RefCell::new([NO_HANDLER; MAX_HANDLERS]));
+
+/// Timer is disabled by the first wake up interrupt
+static ONE_SHOT: AtomicBool = AtomicBool::new(false);
+/// Calendar time when timer was started or the last wake up was handled, in nanoseconds
+/// since [EPOCH], None when timer was not started by [WakeupManager] or calendar was changed
+/// since then
+static LAST_RELOAD: Mutex<Cell<Option<i64>>> = Mutex::new(Cell::new(None));
+/// All wake up events, including missed ones
+static TOTAL_EVENTS: AtomicU32 = AtomicU32::new(0);
+/// Wake up events, that happened while previous one was not handled yet
+static MISSED_EVENTS: AtomicU32 = AtomicU32::new(0);
+/// Set while registered handlers are called
+static DISPATCHING: AtomicBool = AtomicBool::new(false);
+/// Wake ups, for which handlers were not called, because previous ones were still running
+static SKIPPED_DISPATCHES: AtomicU32 = AtomicU32::new(0);
+
+/// Shortest period, for which missed events are estimated from calendar time
+const MIN_ESTIMATED_PERIOD: i64 = NANOS as i64;
+
+/// Calendar time is measured from this date
+const EPOCH: Date = Date::from(1, 1, 2000);
+
+/// Returns calendar time in nanoseconds since [EPOCH]
+fn calendar_nanos(rtc: &RegisterBlock) -> i64 {
+    let now = read_date_time(rtc);
+    (i64::from(now.date.days_since(&EPOCH)) * 86_400 + i64::from(now.time.to_seconds()))
+        * NANOS as i64
+        + i64::from(now.nanosecond)
+}
+
+/// Forgets calendar time of the last wake up, has to be called whenever calendar is written
+/// or shifted, so the jump is not counted as missed events
+pub(crate) fn calendar_changed() {
+    free(|cs| LAST_RELOAD.borrow(cs).set(None));
+}
+
+/// Identifies registered interrupt handler, so it can be removed later
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HandlerId(usize);
+
+/// Errors of handler registration
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HandlerError {
+    /// All [MAX_HANDLERS] slots are already used
+    RegistryFull,
+    /// Handler with given id is not registered
+    NotRegistered,
+    /// Registry cannot be changed from inside of a handler
+    Busy,
+}
 
 /// Contains all WakeUp counter divisions that are available to use
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum WakeupRtcDivision {
     /// When used on WakeUp timer slows counting **16 times**
     RtcDiv16 = 0b000,
@@ -26,6 +115,126 @@ impl WakeupRtcDivision {
     pub fn get_bits(self) -> u8 {
         self as u8
     }
+
+    /// Returns division for bits of WUCKSEL register field, RTC uses only two highest bits
+    /// for clock spare selections
+    /// ```
+    /// use stm32f3_rtc::wakeup::WakeupRtcDivision;
+    /// for division in [
+    ///     WakeupRtcDivision::RtcDiv16,
+    ///     WakeupRtcDivision::RtcDiv8,
+    ///     WakeupRtcDivision::RtcDiv4,
+    ///     WakeupRtcDivision::RtcDiv2,
+    ///     WakeupRtcDivision::RtcNoDiv,
+    ///     WakeupRtcDivision::RtcOffset,
+    /// ] {
+    ///     assert_eq!(WakeupRtcDivision::from_bits(division.get_bits()), division);
+    /// }
+    /// assert_eq!(WakeupRtcDivision::from_bits(0b101), WakeupRtcDivision::RtcNoDiv);
+    /// assert_eq!(WakeupRtcDivision::from_bits(0b111), WakeupRtcDivision::RtcOffset);
+    /// ```
+    pub fn from_bits(bits: u8) -> WakeupRtcDivision {
+        match bits & 0b111 {
+            0b000 => WakeupRtcDivision::RtcDiv16,
+            0b001 => WakeupRtcDivision::RtcDiv8,
+            0b010 => WakeupRtcDivision::RtcDiv4,
+            0b011 => WakeupRtcDivision::RtcDiv2,
+            0b100 | 0b101 => WakeupRtcDivision::RtcNoDiv,
+            _ => WakeupRtcDivision::RtcOffset,
+        }
+    }
+}
+
+/// Wake up timer setup calculated for requested period
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WakeupPeriod {
+    pub division: WakeupRtcDivision,
+    /// Value of wake up counter (WUTR), timer counts **counter + 1** ticks
+    pub counter: u16,
+    /// Period that timer really uses
+    pub period: Duration,
+    /// Achieved period minus requested period in nanoseconds
+    pub error: i64,
+}
+
+impl WakeupPeriod {
+    /// Picks division and counter that give the closest period for RTC clock with given
+    /// frequency in Hz, RTC/2 - RTC/16 are preferred for their resolution. Period is limited
+    /// to range that timer can count (**2 / frequency** to 131072 seconds).
+    /// ```
+    /// use std::time::Duration;
+    /// use stm32f3_rtc::wakeup::{WakeupPeriod, WakeupRtcDivision};
+    /// // 32.768 kHz LSE
+    /// let setup = WakeupPeriod::calculate(Duration::from_millis(100), 32_768);
+    /// assert_eq!((setup.division, setup.counter), (WakeupRtcDivision::RtcDiv2, 1637));
+    /// assert_eq!(setup.period, Duration::from_nanos(99_975_586));
+    /// assert_eq!(setup.error, -24_414);
+    ///
+    /// let setup = WakeupPeriod::calculate(Duration::from_secs(10), 32_768);
+    /// assert_eq!((setup.division, setup.counter), (WakeupRtcDivision::RtcDiv8, 40_959));
+    /// assert_eq!(setup.error, 0);
+    ///
+    /// let setup = WakeupPeriod::calculate(Duration::from_secs(3600), 32_768);
+    /// assert_eq!((setup.division, setup.counter), (WakeupRtcDivision::RtcNoDiv, 3599));
+    ///
+    /// let setup = WakeupPeriod::calculate(Duration::from_secs(36 * 3600), 32_768);
+    /// assert_eq!((setup.division, setup.counter), (WakeupRtcDivision::RtcOffset, 64_063));
+    ///
+    /// // Shortest and longest period
+    /// let setup = WakeupPeriod::calculate(Duration::from_nanos(1), 32_768);
+    /// assert_eq!(setup.period, Duration::from_nanos(61_035));
+    /// let setup = WakeupPeriod::calculate(Duration::from_secs(200_000), 32_768);
+    /// assert_eq!(setup.period, Duration::from_secs(131_072));
+    /// ```
+    pub fn calculate(period: Duration, frequency: u32) -> WakeupPeriod {
+        let requested = period.as_nanos();
+        let frequency = u128::from(frequency.max(1));
+        let divisions = [
+            (WakeupRtcDivision::RtcDiv2, 2),
+            (WakeupRtcDivision::RtcDiv4, 4),
+            (WakeupRtcDivision::RtcDiv8, 8),
+            (WakeupRtcDivision::RtcDiv16, 16),
+        ];
+        for &(division, divider) in divisions.iter() {
+            // Ticks rounded to the closest value
+            let ticks = (requested * frequency + divider * 500_000_000) / (divider * NANOS);
+            if ticks <= 0x1_0000 {
+                return Self::from_ticks(division, ticks.max(1), divider, frequency, requested);
+            }
+        }
+        let seconds = ((requested + NANOS / 2) / NANOS).min(0x2_0000);
+        match seconds <= 0x1_0000 {
+            true => Self::from_ticks(WakeupRtcDivision::RtcNoDiv, seconds, 1, 1, requested),
+            false => {
+                let mut setup = Self::from_ticks(
+                    WakeupRtcDivision::RtcOffset,
+                    seconds - 0x1_0000,
+                    1,
+                    1,
+                    requested,
+                );
+                setup.period += Duration::from_secs(0x1_0000);
+                setup.error += 0x1_0000 * NANOS as i64;
+                setup
+            }
+        }
+    }
+
+    fn from_ticks(
+        division: WakeupRtcDivision,
+        ticks: u128,
+        divider: u128,
+        frequency: u128,
+        requested: u128,
+    ) -> WakeupPeriod {
+        let achieved = (ticks * divider * NANOS + frequency / 2) / frequency;
+        WakeupPeriod {
+            division,
+            counter: (ticks - 1) as u16,
+            period: Duration::from_nanos(achieved as u64),
+            error: achieved as i64 - requested as i64,
+        }
+    }
 }
 
 /// By using this struct you can easily set up your WakeUp timer and interrupt.
@@ -63,25 +272,37 @@ impl WakeupRtcDivision {
 ///     .set_counter(200)
 ///     .set_interrupt(true,peripheral.EXTI)
 ///     .enable();
-/// WakeupManager::set_interrupt_handler(|| {hprintln!("Interupt handler works")})
+/// WakeupManager::set_interrupt_handler(|| {hprintln!("Interupt handler works")}).unwrap();
 /// ```
+/// 3. With `external-wakeup-interrupt` feature the crate does not define `RTC_WKUP` interrupt,
+///    so application (e.g. RTIC task bound to it) owns the vector and calls
+///    [on_wakeup_interrupt] from it.
 pub struct WakeupManager<'a> {
-    rtc: &'a mut Rtc,
+    rtc: &'a RegisterBlock,
     sel: u8,
     time: u16,
     interrupt: RtcInterrupt,
     en_interrupt: bool,
+    period: Option<WakeupPeriod>,
+    one_shot: bool,
 }
 
 impl<'a> WakeupManager<'a> {
     /// Returns new WakeupManager instance
     pub fn new(rtc: &'a mut Rtc) -> WakeupManager<'a> {
+        Self::from_registers(&rtc.rtc)
+    }
+
+    /// Returns new WakeupManager instance working directly on RTC registers
+    pub(crate) fn from_registers(rtc: &'a RegisterBlock) -> WakeupManager<'a> {
         Self {
             rtc,
             sel: WakeupRtcDivision::RtcNoDiv.get_bits(),
             time: 360,
             interrupt: RtcInterrupt::new(),
             en_interrupt: false,
+            period: None,
+            one_shot: false,
         }
     }
     /// Configure your Interrupt by setting output and polarity.
@@ -135,7 +356,7 @@ impl<'a> WakeupManager<'a> {
     /// ## Example:
     /// ### 1:
     /// ```
-    /// WakeupManager::set_interrupt_handler(|| {hprintln!("Interupt handler works")})
+    /// WakeupManager::set_interrupt_handler(|| {hprintln!("Interupt handler works")}).unwrap();
     /// ```
     /// ### 2:
     /// ```
@@ -143,63 +364,253 @@ impl<'a> WakeupManager<'a> {
     /// hprintln!("My number: {}", number);
     /// }
     /// ...
-    /// WakeupManager::set_interrupt_handler(|| handler(3))
+    /// WakeupManager::set_interrupt_handler(|| handler(3)).unwrap();
+    /// ```
+    ///
+    /// **Note:** It replaces all handlers that are already registered. Returns
+    /// **HandlerError::Busy** and keeps registered handlers, when it is called from inside
+    /// of a handler.
+    pub fn set_interrupt_handler(function: fn()) -> Result<HandlerId, HandlerError> {
+        modify_handlers(|handlers| {
+            *handlers = [NO_HANDLER; MAX_HANDLERS];
+            handlers[0] = Some(Handler::Function(function));
+            Ok(HandlerId(0))
+        })
+    }
+
+    /// Returns number of wake up events handled by [on_wakeup_interrupt] (directly or by
+    /// `RTC_WKUP` interrupt), including missed ones
+    pub fn total_events() -> u32 {
+        TOTAL_EVENTS.load(Ordering::Relaxed)
+    }
+
+    /// Returns number of wake up events, that were missed because interrupt was handled later
+    /// than one period after the previous one (e.g. when interrupts were masked during flash
+    /// erase). Periodic code can use it to compensate skipped samples.
+    ///
+    /// **Note:** Missed events are estimated from calendar time, so they are counted only for
+    /// periods of at least 1 second. Writing or shifting calendar (e.g. by **set_time()** or
+    /// **apply_summer_time()**) restarts the estimation at the next wake up.
+    /// ```
+    /// let missed = WakeupManager::missed_events();
+    /// if missed > last_missed {
+    ///     samples.skip(missed - last_missed);
+    ///     last_missed = missed;
+    /// }
+    /// ```
+    pub fn missed_events() -> u32 {
+        MISSED_EVENTS.load(Ordering::Relaxed)
+    }
+
+    /// Returns number of wake ups, for which handlers were not called, because handlers of
+    /// the previous one were still running (e.g. [on_wakeup_interrupt] was called from a
+    /// handler or from higher priority interrupt)
+    pub fn skipped_dispatches() -> u32 {
+        SKIPPED_DISPATCHES.load(Ordering::Relaxed)
+    }
+
+    /// Sets all event counters to zero
+    pub fn reset_event_counters() {
+        TOTAL_EVENTS.store(0, Ordering::Relaxed);
+        MISSED_EVENTS.store(0, Ordering::Relaxed);
+        SKIPPED_DISPATCHES.store(0, Ordering::Relaxed);
+    }
+
+    /// Adds handler to the ones called on wake up interrupt, handlers are called in order
+    /// of registration
+    /// ```
+    /// let id = WakeupManager::add_interrupt_handler(|| hprintln!("Wake up")).unwrap();
+    /// ```
+    pub fn add_interrupt_handler(function: fn()) -> Result<HandlerId, HandlerError> {
+        add_handler(Handler::Function(function))
+    }
+
+    /// Adds handler that gets mutable access to its own context on every wake up
+    /// ```
+    /// fn count(counter: &mut u32) {
+    ///     *counter += 1;
+    /// }
+    /// ...
+    /// let counter = cortex_m::singleton!(: u32 = 0).unwrap();
+    /// let id = WakeupManager::add_context_handler(count, counter).unwrap();
     /// ```
-    pub fn set_interrupt_handler(function: fn()) {
-        unsafe { INSTANCE = Some(function) }
+    pub fn add_context_handler<T: Send>(
+        function: fn(&mut T),
+        context: &'static mut T,
+    ) -> Result<HandlerId, HandlerError> {
+        add_handler(Handler::Context {
+            call: call_with_context::<T>,
+            function: function as *const (),
+            context: context as *mut T as *mut (),
+        })
+    }
+
+    /// Removes registered handler, context of removed handler is not accessible any more
+    /// ```
+    /// WakeupManager::remove_interrupt_handler(id).unwrap();
+    /// ```
+    pub fn remove_interrupt_handler(id: HandlerId) -> Result<(), HandlerError> {
+        modify_handlers(|handlers| {
+            let removed = handlers.get_mut(id.0).and_then(Option::take);
+            removed.map(|_| ()).ok_or(HandlerError::NotRegistered)
+        })
     }
 
     /// Please set counter for your WakeUp event. Every time counter will finish
     /// counting time that you set WakeUp event will happen.
     pub fn set_counter(mut self, time: u16) -> Self {
         self.time = time;
+        self.period = None;
+        self
+    }
+
+    /// Set period of WakeUp events, division and counter are picked for RTC clock frequency
+    /// given by prescalers (**(PREDIV_A + 1) * (PREDIV_S + 1)**). Periods from 2 RTC clock
+    /// cycles (~61 µs with LSE) up to 131072 seconds (~36 hours) are supported.
+    /// ```
+    /// let wakeup = rtc.get_wakeup_manager().set_period(Duration::from_millis(250));
+    /// let period = wakeup.achieved_period().unwrap();
+    /// hprintln!("Period: {:?}, error: {} ns", period.period, period.error);
+    /// wakeup.enable();
+    /// ```
+    pub fn set_period(mut self, period: Duration) -> Self {
+        self.apply_period(period);
         self
     }
 
+    /// Wake up only once, timer is disabled by the first wake up interrupt, so interrupt has
+    /// to be enabled
+    pub fn set_one_shot(mut self, one_shot: bool) -> Self {
+        self.one_shot = one_shot;
+        self
+    }
+
+    /// Returns setup calculated by **set_period()**, None when counter or division was set
+    /// directly
+    pub fn achieved_period(&self) -> Option<WakeupPeriod> {
+        self.period
+    }
+
 
     /// You can set division for your RTC clock that will affect by slowing down
     /// WukeUp timer. Please read **WakeupRtcDivision** documentation.
     ///
     pub fn set_clock_division(mut self, division: WakeupRtcDivision) -> Self {
         self.sel = division.get_bits();
+        self.period = None;
         self
     }
 
     /// Enable wakeup timer. I can be reused to reconfigure the timer.
-    pub fn enable(mut self) -> Self {
-        // Disable Wakeup Timer and waiting for ready flag
-        self.rtc.rtc.cr.modify(|_, w| w.wute().disabled());
-        while self.rtc.rtc.isr.read().wutwf().is_update_not_allowed() {}
+    pub fn enable(self) -> Self {
+        self.start(true);
+        self
+    }
+
+    /// Changes period of running timer, interrupt setup stays the same. Timer starts counting
+    /// new period from now.
+    /// ```
+    /// let mut wakeup = rtc.get_wakeup_manager().set_period(Duration::from_secs(1)).enable();
+    /// ...
+    /// // Nothing to do, sample less often
+    /// wakeup.reschedule(Duration::from_secs(10));
+    /// ```
+    pub fn reschedule(&mut self, period: Duration) -> WakeupPeriod {
+        let setup = self.apply_period(period);
+        self.start(false);
+        setup
+    }
+
+    /// Disables wakeup timer
+    pub fn disable(&mut self) {
+        unprotected(self.rtc, |rtc| rtc.cr.modify(|_, w| w.wute().disabled()))
+    }
+
+    /// Returns true when wakeup timer is running
+    pub fn is_enabled(&self) -> bool {
+        self.rtc.cr.read().wute().is_enabled()
+    }
+
+    /// Returns period that wake up timer uses now, calculated from RTC registers
+    pub fn active_period(&self) -> Duration {
+        active_period(self.rtc)
+    }
+
+    /// Returns estimated time to the next wake up, None when timer is disabled, it was not
+    /// started by WakeupManager or calendar was changed since the last wake up. Counter
+    /// position is not readable, so it is estimated from calendar time elapsed since timer was
+    /// started or last wake up was handled, with resolution of the RTC sub second counter.
+    pub fn time_remaining(&self) -> Option<Duration> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let period = self.active_period().as_nanos() as u64;
+        let started = free(|cs| LAST_RELOAD.borrow(cs).get())?;
+        let elapsed = calendar_nanos(self.rtc).saturating_sub(started) as u64;
+        Some(Duration::from_nanos(period - elapsed % period.max(1)))
+    }
+
+    /// Calculates division and counter for given period and keeps them for next start
+    fn apply_period(&mut self, period: Duration) -> WakeupPeriod {
+        let setup = WakeupPeriod::calculate(period, frequency(self.rtc));
+        self.sel = setup.division.get_bits();
+        self.time = setup.counter;
+        self.period = Some(setup);
+        setup
+    }
+
+    /// Writes setup into registers and starts timer
+    fn start(&self, configure_interrupt: bool) {
+        unprotected(self.rtc, |rtc| self.program(rtc, configure_interrupt));
+        while self.rtc.isr.read().wutwf().is_update_allowed() {}
+        ONE_SHOT.store(self.one_shot, Ordering::Relaxed);
+        let now = calendar_nanos(self.rtc);
+        free(|cs| LAST_RELOAD.borrow(cs).set(Some(now)));
+    }
+
+    /// Writes division, counter and interrupt setup and enables timer, write protection has
+    /// to be disabled
+    fn program(&self, rtc: &RegisterBlock, configure_interrupt: bool) {
+        // Disable Wakeup Timer and waiting for ready flag, then clock selection and
+        // counter can be written without init mode
+        rtc.cr.modify(|_, w| w.wute().disabled());
+        while rtc.isr.read().wutwf().is_update_not_allowed() {}
         self.set_wutsel();
-        self.rtc.write_protection(Protection::Disable);
         // Interrupt enabling
-        match self.en_interrupt {
-            true => self.enable_interrupts(),
-            false => self.rtc.rtc.cr.modify(|_, w| w.wutie().disabled()),
+        match (configure_interrupt, self.en_interrupt) {
+            (false, _) => {}
+            (true, true) => self.enable_interrupts(),
+            (true, false) => rtc.cr.modify(|_, w| w.wutie().disabled()),
         }
         self.set_time();
-        self.rtc.rtc.cr.modify(|_, w| w.wute().enabled());
-        self.rtc.rtc.isr.modify(|_, w| w.wutf().bit(false));
-        self.rtc.write_protection(Protection::Enable);
-        while self.rtc.rtc.isr.read().wutwf().is_update_allowed() {}
-        self
+        rtc.cr.modify(|_, w| w.wute().enabled());
+        rtc.isr.modify(|_, w| w.wutf().bit(false));
     }
 
-    fn set_wutsel(&mut self) {
-        self.rtc
-            .modify(|rtc| rtc.cr.modify(|_, w| w.wucksel().clock_spare()));
+    /// Returns division that wake up timer uses now, read from RTC register
+    pub fn active_division(&self) -> WakeupRtcDivision {
+        WakeupRtcDivision::from_bits(self.rtc.cr.read().wucksel().bits())
+    }
+
+    /// Returns counter (WUTR) that wake up timer uses now, read from RTC register
+    pub fn active_counter(&self) -> u16 {
+        self.rtc.wutr.read().wut().bits()
     }
 
-    fn set_time(&mut self) {
+    fn set_wutsel(&self) {
+        // Selection comes from WakeupRtcDivision, so it is always valid
         self.rtc
-            .rtc
-            .wutr
-            .modify(|_, w| w.wut().bits(self.time as u16));
+            .cr
+            .modify(|_, w| unsafe { w.wucksel().bits(self.sel) });
     }
 
-    fn enable_interrupts(&mut self) {
-        self.rtc.rtc.cr.modify(|_, w| w.wutie().enabled());
-        self.rtc.rtc.cr.modify(|_, w| {
+    fn set_time(&self) {
+        self.rtc.wutr.modify(|_, w| w.wut().bits(self.time as u16));
+    }
+
+    fn enable_interrupts(&self) {
+        self.rtc.cr.modify(|_, w| w.wutie().enabled());
+        self.rtc.cr.modify(|_, w| {
             w.osel()
                 .bits(self.interrupt.output_selection.clone().into());
             w.pol().bit(self.interrupt.polarity.clone().into())
@@ -207,12 +618,236 @@ impl<'a> WakeupManager<'a> {
     }
 }
 
+/// Returns RTC clock frequency given by prescalers
+fn frequency(rtc: &RegisterBlock) -> u32 {
+    let prer = rtc.prer.read();
+    (u32::from(prer.prediv_a().bits()) + 1) * (u32::from(prer.prediv_s().bits()) + 1)
+}
+
+/// Returns period that wake up timer uses now
+fn active_period(rtc: &RegisterBlock) -> Duration {
+    let ticks = u128::from(rtc.wutr.read().wut().bits()) + 1;
+    let nanos = match WakeupRtcDivision::from_bits(rtc.cr.read().wucksel().bits()) {
+        WakeupRtcDivision::RtcNoDiv => ticks * NANOS,
+        WakeupRtcDivision::RtcOffset => (ticks + 0x1_0000) * NANOS,
+        division => {
+            // Division bits 0 - 3 are for RTC/16 - RTC/2
+            let divider = 16 >> division.get_bits();
+            let frequency = u128::from(frequency(rtc));
+            // Rounded the same way as period calculated by WakeupPeriod
+            (ticks * divider * NANOS + frequency / 2) / frequency
+        }
+    };
+    Duration::from_nanos(nanos as u64)
+}
+
+/// Counts wake up events, that happened since the last handled one. Wake up flag keeps only
+/// one event, so events missed by late handler are found from elapsed calendar time. It is
+/// not estimated for periods shorter than [MIN_ESTIMATED_PERIOD] (interrupt latency is
+/// comparable with them) and after calendar was changed.
+fn count_events(rtc: &RegisterBlock) {
+    let period = active_period(rtc).as_nanos() as i64;
+    let now = calendar_nanos(rtc);
+    let last = free(|cs| LAST_RELOAD.borrow(cs).replace(Some(now)));
+    let events = match last {
+        // Rounded, so jitter of interrupt latency is not counted as missed event
+        Some(last) if period >= MIN_ESTIMATED_PERIOD && now > last => {
+            ((now - last + period / 2) / period).max(1) as u32
+        }
+        _ => 1,
+    };
+    TOTAL_EVENTS.fetch_add(events, Ordering::Relaxed);
+    MISSED_EVENTS.fetch_add(events - 1, Ordering::Relaxed);
+}
+
+/// Changes registered handlers in critical section, registry cannot be changed while
+/// handlers are called
+fn modify_handlers<F, R>(function: F) -> Result<R, HandlerError>
+where
+    F: FnOnce(&mut [Option<Handler>; MAX_HANDLERS]) -> Result<R, HandlerError>,
+{
+    if DISPATCHING.load(Ordering::Acquire) {
+        return Err(HandlerError::Busy);
+    }
+    free(|cs| function(&mut HANDLERS.borrow(cs).borrow_mut()))
+}
+
+/// Puts handler into the first free slot
+fn add_handler(handler: Handler) -> Result<HandlerId, HandlerError> {
+    modify_handlers(|handlers| match handlers.iter().position(Option::is_none) {
+        Some(slot) => {
+            handlers[slot] = Some(handler);
+            Ok(HandlerId(slot))
+        }
+        None => Err(HandlerError::RegistryFull),
+    })
+}
+
+/// Handles wake up interrupt: calls registered handlers and clears wake up flags.
+///
+/// It is called by `RTC_WKUP` interrupt defined by this crate, with `external-wakeup-interrupt`
+/// feature application has to call it from its own `RTC_WKUP` handler.
+///
+/// **Note:** Handlers are called with interrupts enabled, registered handlers cannot be changed
+/// from inside of them. When it is called again before handlers return, handlers are not
+/// called and the call is counted by **WakeupManager::skipped_dispatches()**.
+/// ```
+/// #[interrupt]
+/// fn RTC_WKUP() {
+///     stm32f3_rtc::wakeup::on_wakeup_interrupt();
+/// }
+/// ```
+pub fn on_wakeup_interrupt() {
+    let rtc = unsafe { &*RTC::PTR };
+    // Flags are cleared before handlers are called, so event that comes while they run sets
+    // them again and interrupt is entered once more instead of the event being lost. ISR is
+    // read-modify-written and other RTC users may change it at the same time.
+    let flagged = free(|_| {
+        let flagged = rtc.isr.read().wutf().bit_is_set();
+        rtc.isr.modify(|_, w| w.wutf().clear_bit());
+        flagged
+    });
+    // Pending bits are cleared by writing 1, so only line 20 is written
+    unsafe { (*EXTI::PTR).pr1.write(|w| w.pr20().set_bit()) };
+    // Interrupt can come also from other EXTI 20 source, then there is no event to count
+    // and one-shot timer is still pending
+    if flagged {
+        count_events(rtc);
+        if ONE_SHOT.load(Ordering::Relaxed) {
+            unprotected(rtc, |rtc| rtc.cr.modify(|_, w| w.wute().disabled()));
+        }
+    }
+    // Context handlers keep exclusive access to their context, so they are not called again
+    // until the previous call returns
+    if DISPATCHING.swap(true, Ordering::Acquire) {
+        SKIPPED_DISPATCHES.fetch_add(1, Ordering::Relaxed);
+        return;
+    }
+    // Handlers are copied, so they are called with interrupts enabled
+    let handlers = free(|cs| *HANDLERS.borrow(cs).borrow());
+    for handler in handlers.iter().flatten() {
+        match *handler {
+            Handler::Function(function) => function(),
+            Handler::Context {
+                call,
+                function,
+                context,
+            } => call(function, context),
+        }
+    }
+    DISPATCHING.store(false, Ordering::Release);
+}
+
+#[cfg(not(feature = "external-wakeup-interrupt"))]
 #[interrupt]
-unsafe fn RTC_WKUP() {
-    match INSTANCE {
-        None => {}
-        Some(_function) => _function(),
+fn RTC_WKUP() {
+    on_wakeup_interrupt();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{active_period, WakeupManager, WakeupRtcDivision};
+    use core::mem;
+    use core::time::Duration;
+    use stm32f3xx_hal::pac::rtc::RegisterBlock;
+
+    /// Size of RTC register block in words
+    const WORDS: usize = mem::size_of::<RegisterBlock>() / 4;
+    /// Word offsets of registers
+    const CR: usize = 0x08 / 4;
+    const ISR: usize = 0x0C / 4;
+    const PRER: usize = 0x10 / 4;
+
+    /// Register block kept in memory, wake up timer is always ready to be written
+    struct SimulatedRtc {
+        words: [u32; WORDS],
+    }
+
+    impl SimulatedRtc {
+        /// RTC running from 32.768 kHz LSE with default prescalers
+        fn new() -> SimulatedRtc {
+            let mut words = [0; WORDS];
+            // WUTWF
+            words[ISR] = 1 << 2;
+            words[PRER] = (127 << 16) | 255;
+            SimulatedRtc { words }
+        }
+
+        fn registers(&mut self) -> &RegisterBlock {
+            unsafe { &*(self.words.as_mut_ptr() as *const RegisterBlock) }
+        }
+    }
+
+    #[test]
+    fn period_selects_wucksel_and_wutr() {
+        #[rustfmt::skip]
+        let setups = [
+            (Duration::from_millis(100), WakeupRtcDivision::RtcDiv2, 0b011, 1637),
+            (Duration::from_secs(5), WakeupRtcDivision::RtcDiv4, 0b010, 40_959),
+            (Duration::from_secs(10), WakeupRtcDivision::RtcDiv8, 0b001, 40_959),
+            (Duration::from_secs(20), WakeupRtcDivision::RtcDiv16, 0b000, 40_959),
+            (Duration::from_secs(3600), WakeupRtcDivision::RtcNoDiv, 0b100, 3599),
+            (Duration::from_secs(36 * 3600), WakeupRtcDivision::RtcOffset, 0b110, 64_063),
+        ];
+        for &(period, division, wucksel, wutr) in setups.iter() {
+            let mut simulated = SimulatedRtc::new();
+            let rtc = simulated.registers();
+            let wakeup = WakeupManager::from_registers(rtc).set_period(period);
+            let setup = wakeup.achieved_period().unwrap();
+            assert_eq!((setup.division, setup.counter), (division, wutr as u16));
+            wakeup.program(rtc, true);
+
+            assert_eq!(rtc.cr.read().bits() & 0b111, wucksel, "{:?}", period);
+            assert_eq!(rtc.wutr.read().bits(), wutr, "{:?}", period);
+            // WUTE
+            assert_ne!(rtc.cr.read().bits() & (1 << 10), 0);
+            assert_eq!(wakeup.active_division(), division);
+            assert_eq!(wakeup.active_counter(), wutr as u16);
+            assert_eq!(active_period(rtc), setup.period);
+        }
+    }
+
+    #[test]
+    fn counter_and_division_round_trip() {
+        let divisions = [
+            WakeupRtcDivision::RtcDiv16,
+            WakeupRtcDivision::RtcDiv8,
+            WakeupRtcDivision::RtcDiv4,
+            WakeupRtcDivision::RtcDiv2,
+            WakeupRtcDivision::RtcNoDiv,
+            WakeupRtcDivision::RtcOffset,
+        ];
+        for &division in divisions.iter() {
+            for &counter in [0, 1, 200, u16::MAX].iter() {
+                let mut simulated = SimulatedRtc::new();
+                let rtc = simulated.registers();
+                let wakeup = WakeupManager::from_registers(rtc)
+                    .set_counter(counter)
+                    .set_clock_division(division);
+                assert_eq!(wakeup.achieved_period(), None);
+                wakeup.program(rtc, false);
+
+                assert_eq!(rtc.cr.read().bits() & 0b111, u32::from(division.get_bits()));
+                assert_eq!(wakeup.active_division(), division);
+                assert_eq!(wakeup.active_counter(), counter);
+            }
+        }
+    }
+
+    #[test]
+    fn reprogramming_keeps_other_control_bits() {
+        let mut simulated = SimulatedRtc::new();
+        // FMT (12 hour format) and ALRAE
+        let other_bits = (1 << 6) | (1 << 8);
+        simulated.words[CR] = other_bits;
+        let rtc = simulated.registers();
+        let wakeup = WakeupManager::from_registers(rtc).set_period(Duration::from_secs(10));
+        wakeup.program(rtc, false);
+        let wakeup = wakeup.set_period(Duration::from_millis(100));
+        wakeup.program(rtc, false);
+
+        assert_eq!(rtc.cr.read().bits() & other_bits, other_bits);
+        assert_eq!(wakeup.active_division(), WakeupRtcDivision::RtcDiv2);
+        assert_eq!(wakeup.active_counter(), 1637);
     }
-    (*RTC::PTR).isr.modify(|_, w| w.wutf().clear_bit());
-    (*EXTI::PTR).pr1.modify(|_, w| w.pr20().set_bit());
 }